@@ -6,23 +6,13 @@
 extern crate proc_macro;
 
 use proc_macro::TokenStream;
-use rtic_syntax::Settings;
+use rtic_syntax::testing;
 
 #[proc_macro_attribute]
 pub fn app(args: TokenStream, input: TokenStream) -> TokenStream {
-    let mut settings = Settings::default();
-    let mut rtic_args = vec![];
-    for arg in args.to_string().split(',') {
-        if arg.trim() == "parse_binds" {
-            settings.parse_binds = true;
-        } else if arg.trim() == "parse_extern_interrupt" {
-            settings.parse_extern_interrupt = true;
-        } else {
-            rtic_args.push(arg.to_string());
-        }
-    }
+    let (args, settings) = testing::settings(args.into());
 
-    if let Err(e) = rtic_syntax::parse(rtic_args.join(", ").parse().unwrap(), input, settings) {
+    if let Err(e) = rtic_syntax::parse2(args, input.into(), settings) {
         e.to_compile_error().into()
     } else {
         "fn main() {}".parse().unwrap()
@@ -1,71 +1,149 @@
-use syn::Ident;
+use core::cmp;
+
+use syn::{Ident, Type};
 
 use crate::{
     analyze::Priority,
-    ast::{Access, App, Local, TaskLocal},
+    ast::{
+        Access, App, FieldPath, Local, Monotonic, NormalizedType, SoftwareTask, TaskLocal, TaskRef,
+    },
+    normalize::Imports,
 };
 
 impl App {
-    pub(crate) fn shared_resource_accesses(
-        &self,
-    ) -> impl Iterator<Item = (Option<Priority>, &Ident, Access)> {
-        self.idle
-            .iter()
-            .flat_map(|idle| {
-                idle.args
-                    .shared_resources
+    /// All the execution contexts of the application: `init`, `idle` (if any), the hardware
+    /// tasks and then the software tasks, in declaration order
+    pub fn contexts(&self) -> impl Iterator<Item = TaskRef<'_>> {
+        Some(TaskRef::Init(&self.init))
+            .into_iter()
+            .chain(self.idle.iter().map(TaskRef::Idle))
+            .chain(
+                self.hardware_tasks
                     .iter()
-                    .map(move |(name, access)| (Some(0), name, *access))
-            })
-            .chain(self.hardware_tasks.values().flat_map(|task| {
-                task.args
-                    .shared_resources
+                    .map(|(name, task)| TaskRef::HardwareTask(name, task)),
+            )
+            .chain(
+                self.software_tasks
+                    .iter()
+                    .map(|(name, task)| TaskRef::SoftwareTask(name, task)),
+            )
+    }
+
+    /// Looks up the execution context called `name`
+    pub fn context(&self, name: &Ident) -> Option<TaskRef<'_>> {
+        self.contexts().find(|context| context.name() == name)
+    }
+
+    /// The contexts that access the `#[shared]` or `#[local]` resource called `resource`
+    pub fn tasks_accessing<'a>(
+        &'a self,
+        resource: &'a Ident,
+    ) -> impl Iterator<Item = TaskRef<'a>> + 'a {
+        self.contexts().filter(move |context| {
+            context.shared_resources().any(|(name, _)| name == resource)
+                || context
+                    .shared_fields()
+                    .any(|(path, _)| path.resource == *resource)
+                || context
+                    .local_resources()
                     .iter()
-                    .map(move |(name, access)| (Some(task.args.priority), name, *access))
-            }))
-            .chain(self.software_tasks.values().flat_map(|task| {
-                task.args
-                    .shared_resources
+                    .any(|(name, tl)| name == resource && Self::is_external(tl))
+        })
+    }
+
+    /// The `#[shared]` and `#[local]` resources accessed by the context called `task`, as a whole
+    /// or through some of their fields
+    ///
+    /// Returns `None` if there's no such context
+    pub fn resources_of(&self, task: &Ident) -> Option<Vec<&Ident>> {
+        self.context(task).map(|context| {
+            let mut resources = context
+                .shared_resources()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>();
+
+            for (path, _) in context.shared_fields() {
+                if !resources.contains(&&path.resource) {
+                    resources.push(&path.resource);
+                }
+            }
+
+            resources.extend(
+                context
+                    .local_resources()
                     .iter()
-                    .map(move |(name, access)| (Some(task.args.priority), name, *access))
-            }))
+                    .filter(|(_, tl)| Self::is_external(tl))
+                    .map(|(name, _)| name),
+            );
+
+            resources
+        })
+    }
+
+    /// Pairs `ty` with its canonical form, resolved through the `use` items of the application
+    pub fn normalize_type(&self, ty: &Type) -> NormalizedType {
+        Imports::new(&self.user_imports).normalize(ty)
+    }
+
+    /// The monotonic marked `default = true`, if any
+    pub fn default_monotonic(&self) -> Option<&Monotonic> {
+        self.monotonics
+            .values()
+            .find(|monotonic| monotonic.args.default)
+    }
+
+    // The priority of the handler of the monotonic called `name`: the highest priority of the
+    // software `tasks` that can be scheduled on it (at least 1) unless given explicitly
+    pub(crate) fn monotonic_priority<'a>(
+        &self,
+        name: &Ident,
+        tasks: impl IntoIterator<Item = &'a SoftwareTask>,
+    ) -> Priority {
+        self.monotonics[name].args.priority.unwrap_or_else(|| {
+            tasks
+                .into_iter()
+                .filter(|task| task.can_be_scheduled_on(name))
+                .map(|task| task.args.priority)
+                .fold(1, cmp::max)
+        })
+    }
+
+    pub(crate) fn shared_resource_accesses(
+        &self,
+    ) -> impl Iterator<Item = (Option<Priority>, &Ident, Access)> {
+        self.contexts().flat_map(|context| {
+            let priority = context.priority();
+
+            context
+                .shared_resources()
+                .map(move |(name, access)| (Some(priority), name, *access))
+        })
+    }
+
+    pub(crate) fn shared_field_accesses(
+        &self,
+    ) -> impl Iterator<Item = (Option<Priority>, &FieldPath, Access)> {
+        self.contexts().flat_map(|context| {
+            let priority = context.priority();
+
+            context
+                .shared_fields()
+                .map(move |(path, access)| (Some(priority), path, *access))
+        })
     }
 
     fn is_external(task_local: &TaskLocal) -> bool {
-        match task_local {
-            TaskLocal::External => true,
-            _ => false,
-        }
+        matches!(task_local, TaskLocal::External)
     }
 
     pub(crate) fn local_resource_accesses(&self) -> impl Iterator<Item = &Ident> {
-        self.init
-            .args
-            .local_resources
-            .iter()
-            .filter(|(_, task_local)| Self::is_external(task_local)) // Only check the resources declared in `#[local]`
-            .map(move |(name, _)| name)
-            .chain(self.idle.iter().flat_map(|idle| {
-                idle.args
-                    .local_resources
-                    .iter()
-                    .filter(|(_, task_local)| Self::is_external(task_local)) // Only check the resources declared in `#[local]`
-                    .map(move |(name, _)| name)
-            }))
-            .chain(self.hardware_tasks.values().flat_map(|task| {
-                task.args
-                    .local_resources
-                    .iter()
-                    .filter(|(_, task_local)| Self::is_external(task_local)) // Only check the resources declared in `#[local]`
-                    .map(move |(name, _)| name)
-            }))
-            .chain(self.software_tasks.values().flat_map(|task| {
-                task.args
-                    .local_resources
-                    .iter()
-                    .filter(|(_, task_local)| Self::is_external(task_local)) // Only check the resources declared in `#[local]`
-                    .map(move |(name, _)| name)
-            }))
+        self.contexts().flat_map(|context| {
+            context
+                .local_resources()
+                .iter()
+                .filter(|(_, task_local)| Self::is_external(task_local)) // Only check the resources declared in `#[local]`
+                .map(move |(name, _)| name)
+        })
     }
 
     fn get_declared_local(tl: &TaskLocal) -> Option<&Local> {
@@ -79,38 +157,17 @@ impl App {
     ///
     /// Returns a vector of (task name, resource name, `Local` struct)
     pub fn declared_local_resources(&self) -> Vec<(&Ident, &Ident, &Local)> {
-        self.init
-            .args
-            .local_resources
-            .iter()
-            .filter_map(move |(name, tl)| {
-                Self::get_declared_local(tl).map(|l| (&self.init.name, name, l))
-            })
-            .chain(self.idle.iter().flat_map(|idle| {
-                idle.args
-                    .local_resources
-                    .iter()
-                    .filter_map(move |(name, tl)| {
-                        Self::get_declared_local(tl)
-                            .map(|l| (&self.idle.as_ref().unwrap().name, name, l))
-                    })
-            }))
-            .chain(self.hardware_tasks.iter().flat_map(|(task_name, task)| {
-                task.args
-                    .local_resources
-                    .iter()
-                    .filter_map(move |(name, tl)| {
-                        Self::get_declared_local(tl).map(|l| (task_name, name, l))
-                    })
-            }))
-            .chain(self.software_tasks.iter().flat_map(|(task_name, task)| {
-                task.args
-                    .local_resources
+        self.contexts()
+            .flat_map(|context| {
+                let task_name = context.name();
+
+                context
+                    .local_resources()
                     .iter()
                     .filter_map(move |(name, tl)| {
                         Self::get_declared_local(tl).map(|l| (task_name, name, l))
                     })
-            }))
+            })
             .collect()
     }
 }
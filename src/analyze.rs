@@ -1,78 +1,106 @@
 //! RTIC application analysis
 
 use core::cmp;
-use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    hash::Hash,
+};
 
-use indexmap::IndexMap;
-use quote::format_ident;
-use syn::{Ident, Type};
+use indexmap::{map::Entry, IndexMap};
+use syn::{Attribute, Expr, Ident, Type};
 
 use crate::{
-    ast::{App, LocalResources, TaskLocal},
-    Set,
+    ast::{
+        Access, App, FieldPath, LocalResources, NormalizedType, SharedResource, TaskKind,
+        TaskLocal, TaskRef,
+    },
+    cfg, duration,
+    normalize::Imports,
+    optimize, Map, Set, Settings,
 };
 
-pub(crate) fn app(app: &App) -> Result<Analysis, syn::Error> {
+pub(crate) fn app(app: &App, settings: &Settings) -> Result<Analysis, syn::Error> {
+    let mut error = vec![];
+
+    // Only the contexts and resources enabled in the active configuration are analyzed
+    let mut enabled = |cfgs: &[Attribute]| match cfg::enabled(cfgs, settings.cfgs.as_ref()) {
+        Ok(enabled) => enabled,
+        Err(e) => {
+            error.push(e);
+            false
+        }
+    };
+
+    let contexts = app
+        .contexts()
+        .filter(|context| enabled(context.cfgs()))
+        .collect::<Vec<_>>();
+    let shared_resources = app
+        .shared_resources
+        .iter()
+        .filter(|(_, res)| enabled(&res.cfgs))
+        .map(|(name, res)| (name.clone(), res))
+        .collect::<Map<&SharedResource>>();
+    let local_resources = app
+        .local_resources
+        .iter()
+        .filter(|(_, res)| enabled(&res.cfgs))
+        .map(|(name, res)| (name.clone(), res))
+        .collect::<Map<_>>();
+    let monotonics = app
+        .monotonics
+        .iter()
+        .filter(|(_, monotonic)| enabled(&monotonic.cfgs))
+        .collect::<Vec<_>>();
+    let message_channels = app
+        .message_channels
+        .iter()
+        .filter(|(_, channel)| enabled(&channel.cfgs))
+        .collect::<Vec<_>>();
+
+    // Create the list of used local resource Idents
+    let mut local_resource_locations = IndexMap::new();
+    for context in &contexts {
+        for (name, task_local) in context.local_resources() {
+            let used = match task_local {
+                TaskLocal::External => local_resources.contains_key(name),
+                TaskLocal::Declared(local) => enabled(&local.cfgs),
+            };
+
+            if used {
+                local_resource_locations.insert(name.clone(), Location::Owned);
+            }
+        }
+    }
+
     // Collect all tasks into a vector
     type TaskName = String;
     type Priority = u8;
 
     // The task list is a Tuple (Name, Shared Resources, Local Resources, Priority)
-    let task_resources_list: Vec<(TaskName, Vec<&Ident>, &LocalResources, Priority)> =
-        Some(&app.init)
-            .iter()
-            .map(|ht| ("init".to_string(), Vec::new(), &ht.args.local_resources, 0))
-            .chain(app.idle.iter().map(|ht| {
-                (
-                    "idle".to_string(),
-                    ht.args
-                        .shared_resources
-                        .iter()
-                        .map(|(v, _)| v)
-                        .collect::<Vec<_>>(),
-                    &ht.args.local_resources,
-                    0,
-                )
-            }))
-            .chain(app.software_tasks.iter().map(|(name, ht)| {
-                (
-                    name.to_string(),
-                    ht.args
-                        .shared_resources
-                        .iter()
-                        .map(|(v, _)| v)
-                        .collect::<Vec<_>>(),
-                    &ht.args.local_resources,
-                    ht.args.priority,
-                )
-            }))
-            .chain(app.hardware_tasks.iter().map(|(name, ht)| {
-                (
-                    name.to_string(),
-                    ht.args
-                        .shared_resources
-                        .iter()
-                        .map(|(v, _)| v)
-                        .collect::<Vec<_>>(),
-                    &ht.args.local_resources,
-                    ht.args.priority,
-                )
-            }))
-            .collect();
-
-    // Create the list of task Idents
-    let tasks: Vec<_> = task_resources_list
+    let task_resources_list: Vec<(TaskName, Vec<&Ident>, &LocalResources, Priority)> = contexts
         .iter()
-        .map(|x| format_ident!("{}", x.0))
+        .map(|context| {
+            (
+                context.name().to_string(),
+                enabled_shared_resources(*context, &shared_resources)
+                    .map(|(v, _)| v)
+                    .chain(
+                        enabled_shared_fields(*context, &shared_resources)
+                            .map(|(path, _)| &path.resource),
+                    )
+                    .collect::<Vec<_>>(),
+                context.local_resources(),
+                context.priority(),
+            )
+        })
         .collect();
 
-    let mut error = vec![];
     let mut lf_res_with_error = vec![];
     let mut lf_hash = HashMap::new();
 
     // Collect lock free resources
-    let lock_free: Vec<&Ident> = app
-        .shared_resources
+    let lock_free: Vec<&Ident> = shared_resources
         .iter()
         .filter(|(_, r)| r.properties.lock_free)
         .map(|(i, _)| i)
@@ -170,101 +198,331 @@ pub(crate) fn app(app: &App) -> Result<Analysis, syn::Error> {
         ));
     }
 
+    // Check that every channel has at most one receiver
+    for (name, _) in &message_channels {
+        let receivers = contexts
+            .iter()
+            .flat_map(|context| context.recv_channels().filter(|channel| channel == name));
+
+        for receiver in receivers.skip(1) {
+            error.push(syn::Error::new(
+                receiver.span(),
+                format!(
+                    "Channel {:?} is received from by more than one task; channels are single-consumer",
+                    name.to_string(),
+                ),
+            ));
+        }
+    }
+
+    // Check that every channel that is sent to has a receiver
+    for (name, _) in &message_channels {
+        if contexts
+            .iter()
+            .any(|context| context.recv_channels().any(|channel| channel == *name))
+        {
+            continue;
+        }
+
+        let senders = contexts
+            .iter()
+            .flat_map(|context| context.send_channels().filter(|channel| channel == name));
+        for sender in senders {
+            error.push(syn::Error::new(
+                sender.span(),
+                format!(
+                    "Channel {:?} is sent to but no task receives from it; add it to the `recv` list of a task",
+                    name.to_string(),
+                ),
+            ));
+        }
+    }
+
     // Collect errors if any and return/halt
     if !error.is_empty() {
-        let mut err = error.iter().next().unwrap().clone();
+        let mut err = error.first().unwrap().clone();
         error.iter().for_each(|e| err.combine(e.clone()));
         return Err(err);
     }
 
+    // Types are compared by their canonical form so that each of them is listed once
+    let imports = Imports::new(&app.user_imports);
+
     // e. Location of resources
     let mut shared_resource_locations = IndexMap::new();
     let mut ownerships = Ownerships::new();
     let mut sync_types = SyncTypes::new();
-    for (prio, name, access) in app.shared_resource_accesses() {
-        let res = app.shared_resources.get(name).expect("UNREACHABLE");
-
-        // (e)
-        // Add each resource to shared_resource_locations
-        shared_resource_locations.insert(name.clone(), Location::Owned);
-
-        // (c)
-        if let Some(priority) = prio {
-            if let Some(ownership) = ownerships.get_mut(name) {
-                match *ownership {
-                    Ownership::Owned { priority: ceiling }
-                    | Ownership::CoOwned { priority: ceiling }
-                    | Ownership::Contended { ceiling }
-                        if priority != ceiling =>
-                    {
-                        *ownership = Ownership::Contended {
-                            ceiling: cmp::max(ceiling, priority),
-                        };
-
-                        if access.is_shared() {
-                            sync_types.insert(res.ty.clone());
-                        }
-                    }
+    for context in &contexts {
+        let priority = context.priority();
 
-                    Ownership::Owned { priority: ceil } if ceil == priority => {
-                        *ownership = Ownership::CoOwned { priority };
-                    }
+        for (name, access) in enabled_shared_resources(*context, &shared_resources) {
+            let res = shared_resources[name];
 
-                    _ => {}
-                }
-            } else {
-                ownerships.insert(name.clone(), Ownership::Owned { priority });
+            // (e)
+            // Add each resource to shared_resource_locations
+            shared_resource_locations.insert(name.clone(), Location::Owned);
+
+            // (c)
+            if own(&mut ownerships, name.clone(), priority) && access.is_shared() {
+                sync_types.insert(imports.normalize(&res.ty));
             }
         }
     }
 
-    // Create the list of used local resource Idents
-    let mut local_resource_locations = IndexMap::new();
+    // Fields locked on their own get ceilings of their own
+    let mut field_ownerships = FieldOwnerships::new();
+    for context in &contexts {
+        let priority = context.priority();
+
+        for (path, access) in enabled_shared_fields(*context, &shared_resources) {
+            let res = shared_resources[&path.resource];
 
-    for (_, _, locals, _) in task_resources_list {
-        for (local, _) in locals {
-            local_resource_locations.insert(local.clone(), Location::Owned);
+            shared_resource_locations.insert(path.resource.clone(), Location::Owned);
+
+            // The type of the field is not known here; require the whole resource to be `Sync`
+            if own(&mut field_ownerships, path.clone(), priority) && access.is_shared() {
+                sync_types.insert(imports.normalize(&res.ty));
+            }
         }
     }
 
-    // Most shared resources need to be `Send`
+    // Ceilings that can't be enforced by masking interrupts selectively; a resource is listed if
+    // the whole of it or any of its fields needs a global critical section
+    let profile = settings.target.profile();
+    let critical_sections = ownerships
+        .iter()
+        .chain(
+            field_ownerships
+                .iter()
+                .map(|(path, ownership)| (&path.resource, ownership)),
+        )
+        .filter_map(|(name, ownership)| match *ownership {
+            Ownership::Contended { ceiling } if profile.needs_global_critical_section(ceiling) => {
+                Some(name.clone())
+            }
+            _ => None,
+        })
+        .collect::<CriticalSections>();
+
+    // How each context accesses each of its shared resources
+    let mut locks = Locks::new();
+    for context in &contexts {
+        let priority = context.priority();
+        let entries = enabled_shared_resources(*context, &shared_resources)
+            .map(|(name, access)| {
+                let ownership = ownerships[name];
+                let ceiling = match ownership {
+                    Ownership::Owned { priority } | Ownership::CoOwned { priority } => priority,
+                    Ownership::Contended { ceiling } => ceiling,
+                };
+                let needs_lock = ownership.needs_lock(priority);
+
+                (
+                    name.clone(),
+                    Lock {
+                        access: *access,
+                        priority,
+                        ceiling,
+                        needs_lock,
+                        critical_section: needs_lock
+                            && profile.needs_global_critical_section(ceiling),
+                        lock_free: shared_resources[name].properties.lock_free,
+                        read_only: access.is_shared(),
+                    },
+                )
+            })
+            .collect();
+
+        locks.insert(context.name().clone(), entries);
+    }
+
+    // Same for the fields
+    let mut field_locks = FieldLocks::new();
+    for context in &contexts {
+        let priority = context.priority();
+        let entries = enabled_shared_fields(*context, &shared_resources)
+            .map(|(path, access)| {
+                let ownership = field_ownerships[path];
+                let ceiling = match ownership {
+                    Ownership::Owned { priority } | Ownership::CoOwned { priority } => priority,
+                    Ownership::Contended { ceiling } => ceiling,
+                };
+                let needs_lock = ownership.needs_lock(priority);
+
+                (
+                    path.clone(),
+                    Lock {
+                        access: *access,
+                        priority,
+                        ceiling,
+                        needs_lock,
+                        critical_section: needs_lock
+                            && profile.needs_global_critical_section(ceiling),
+                        lock_free: shared_resources[&path.resource].properties.lock_free,
+                        read_only: access.is_shared(),
+                    },
+                )
+            })
+            .collect();
+
+        field_locks.insert(context.name().clone(), entries);
+    }
+
+    // Values need to be `Send` when they move from a context to another one that runs at a
+    // different priority. `init` runs at priority 0, before any task, and initializes the
+    // `#[shared]` and `#[local]` resources
     let mut send_types = SendTypes::new();
-    let owned_by_idle = Ownership::Owned { priority: 0 };
-    for (name, res) in app.shared_resources.iter() {
-        // handle not owned by idle
-        if ownerships
-            .get(name)
-            .map(|ownership| *ownership != owned_by_idle)
-            .unwrap_or(false)
-        {
-            send_types.insert(res.ty.clone());
+    let mut require_send = |ty: &Type, reason: SendReason| {
+        send_types
+            .entry(imports.normalize(ty))
+            .or_default()
+            .push(reason);
+    };
+
+    for (name, res) in shared_resources.iter() {
+        let users = contexts
+            .iter()
+            .filter(|context| {
+                context.shared_resources().any(|(res, _)| res == name)
+                    || context
+                        .shared_fields()
+                        .any(|(path, _)| path.resource == *name)
+            })
+            .map(|context| (context.name().clone(), context.priority()))
+            .collect::<Vec<_>>();
+
+        if users.iter().any(|(_, priority)| *priority != 0) {
+            require_send(
+                &res.ty,
+                SendReason::SharedResource {
+                    resource: name.clone(),
+                    users,
+                },
+            );
         }
     }
 
-    // Most local resources need to be `Send` as well
-    for (name, res) in app.local_resources.iter() {
-        if let Some(idle) = &app.idle {
-            // Only Send if not in idle
-            if idle.args.local_resources.get(name).is_none() {
-                send_types.insert(res.ty.clone());
-            }
-        } else {
-            send_types.insert(res.ty.clone());
+    for (name, res) in local_resources.iter() {
+        let users = contexts
+            .iter()
+            .filter(|context| {
+                matches!(
+                    context.local_resources().get(name),
+                    Some(TaskLocal::External)
+                )
+            })
+            .map(|context| (context.name().clone(), context.priority()))
+            .collect::<Vec<_>>();
+
+        if users.iter().any(|(_, priority)| *priority != 0) {
+            require_send(
+                &res.ty,
+                SendReason::LocalResource {
+                    resource: name.clone(),
+                    users,
+                },
+            );
         }
     }
 
+    // Message channels used by at least one task, with their endpoints; a used channel always
+    // has a receiver
+    let mut message_channel_infos = MessageChannels::new();
+    for (name, channel) in message_channels {
+        let receiver = match contexts
+            .iter()
+            .find(|context| context.recv_channels().any(|channel| channel == name))
+        {
+            Some(context) => (context.name().clone(), context.priority()),
+            None => continue,
+        };
+        let senders = contexts
+            .iter()
+            .filter(|context| context.send_channels().any(|channel| channel == name))
+            .map(|context| (context.name().clone(), context.priority()))
+            .collect::<Vec<_>>();
+
+        let mut channel_ownerships = IndexMap::new();
+        for (_, priority) in senders.iter().chain(Some(&receiver)) {
+            own(&mut channel_ownerships, name, *priority);
+        }
+        let ownership = channel_ownerships[name];
+
+        // Messages move from the senders to the receiver
+        if senders.iter().any(|(_, priority)| *priority != receiver.1) {
+            require_send(
+                &channel.ty,
+                SendReason::MessageChannel {
+                    channel: name.clone(),
+                    senders: senders.clone(),
+                    receiver: receiver.clone(),
+                },
+            );
+        }
+
+        message_channel_infos.insert(
+            name.clone(),
+            MessageChannelInfo {
+                ty: channel.ty.clone(),
+                capacity: channel.args.capacity,
+                senders,
+                receiver,
+                ownership,
+            },
+        );
+    }
+
     let mut channels = Channels::new();
 
-    for (name, spawnee) in &app.software_tasks {
+    let software_tasks = contexts
+        .iter()
+        .filter_map(|context| match context {
+            TaskRef::SoftwareTask(name, task) => Some((*name, *task)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    for (name, spawnee) in &software_tasks {
         let spawnee_prio = spawnee.args.priority;
 
         let channel = channels.entry(spawnee_prio).or_default();
-        channel.tasks.insert(name.clone());
+        channel.tasks.insert((*name).clone());
 
-        // All inputs are now send as we do not know from where they may be spawned.
-        spawnee.inputs.iter().for_each(|input| {
-            send_types.insert(input.ty.clone());
-        });
+        // The messages move from the spawners to the task. Tasks that are not in any `spawn` list
+        // can be spawned from any context
+        let declared = app
+            .contexts()
+            .any(|context| context.spawned_tasks().any(|task| task == *name));
+        let spawners = if declared {
+            Some(
+                contexts
+                    .iter()
+                    .filter(|context| context.spawned_tasks().any(|task| task == *name))
+                    .map(|context| (context.name().clone(), context.priority()))
+                    .collect::<Vec<_>>(),
+            )
+        } else {
+            None
+        };
+
+        let crosses_priorities = match &spawners {
+            Some(spawners) => spawners
+                .iter()
+                .any(|(_, priority)| *priority != spawnee_prio),
+            None => true,
+        };
+        if crosses_priorities {
+            for input in &spawnee.inputs {
+                require_send(
+                    &input.ty,
+                    SendReason::TaskInput {
+                        task: (*name).clone(),
+                        priority: spawnee_prio,
+                        spawners: spawners.clone(),
+                    },
+                );
+            }
+        }
     }
 
     // No channel should ever be empty
@@ -279,17 +537,235 @@ pub(crate) fn app(app: &App) -> Result<Analysis, syn::Error> {
             .sum();
     }
 
+    // Timer queues
+    let mut timer_queues = TimerQueues::new();
+    for (name, _) in monotonics {
+        let tasks = software_tasks
+            .iter()
+            .filter(|(_, task)| task.can_be_scheduled_on(name))
+            .collect::<Vec<_>>();
+
+        timer_queues.insert(
+            name.clone(),
+            TimerQueue {
+                tasks: tasks.iter().map(|(name, _)| (*name).clone()).collect(),
+                capacity: tasks
+                    .iter()
+                    .map(|(_, task)| u16::from(task.args.capacity))
+                    .sum(),
+                priority: app.monotonic_priority(name, tasks.iter().map(|(_, task)| *task)),
+            },
+        );
+    }
+
+    // Periodic tasks, with the monotonic they run on
+    let periodic_tasks = software_tasks
+        .iter()
+        .filter_map(|(name, task)| {
+            let period = task.args.period.as_ref()?;
+            let monotonic = match &task.args.monotonic {
+                Some(name) => app.monotonics.get(name)?,
+                None => app.default_monotonic()?,
+            };
+
+            // Literal durations are converted if the tick rate is known
+            let ticks = |duration: &Expr| {
+                if monotonic.args.tick_hz.is_some() && duration::literal(duration).is_some() {
+                    monotonic.duration_to_ticks(duration).ok()
+                } else {
+                    None
+                }
+            };
+
+            Some((
+                (*name).clone(),
+                PeriodicTask {
+                    monotonic: monotonic.ident.clone(),
+                    period: period.clone(),
+                    offset: task.args.offset.clone(),
+                    period_ticks: ticks(period),
+                    offset_ticks: task.args.offset.as_deref().and_then(ticks),
+                },
+            ))
+        })
+        .collect();
+
+    // The queues are internal resources shared by the contexts that spawn tasks, the dispatchers
+    // and the monotonic handlers. A context spawns the tasks it lists in `spawn` and the tasks that
+    // are in no `spawn` list at all, as for the `Send` requirements above; `init` runs with
+    // interrupts disabled so it never uses the queues
+    let can_spawn = |context: &TaskRef<'_>, task: &Task| {
+        let declared = app
+            .contexts()
+            .any(|context| context.spawned_tasks().any(|spawned| spawned == task));
+
+        context.kind() != TaskKind::Init
+            && (!declared || context.spawned_tasks().any(|spawned| spawned == task))
+    };
+    let spawners = |tasks: &BTreeSet<Task>| {
+        contexts
+            .iter()
+            .filter(|context| tasks.iter().any(|task| can_spawn(context, task)))
+            .map(|context| {
+                (
+                    QueueUser::Spawner(context.name().clone()),
+                    context.priority(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    let mut queue_users = IndexMap::new();
+    for (&priority, channel) in &channels {
+        let mut users = spawners(&channel.tasks);
+        for (name, tq) in &timer_queues {
+            if tq.tasks.iter().any(|task| channel.tasks.contains(task)) {
+                users.push((QueueUser::Monotonic(name.clone()), tq.priority));
+            }
+        }
+        users.push((QueueUser::Dispatcher, priority));
+
+        queue_users.insert(Queue::Ready(priority), users);
+    }
+    for (name, tq) in &timer_queues {
+        let mut users = spawners(&tq.tasks);
+        users.push((QueueUser::Monotonic(name.clone()), tq.priority));
+
+        queue_users.insert(Queue::Timer(name.clone()), users);
+    }
+
+    let mut queue_ownerships = IndexMap::new();
+    for (queue, users) in &queue_users {
+        for (_, priority) in users {
+            own(&mut queue_ownerships, queue.clone(), *priority);
+        }
+    }
+    let queues = queue_users
+        .into_iter()
+        .map(|(queue, users)| {
+            let ownership = queue_ownerships[&queue];
+            (queue, QueueResource { users, ownership })
+        })
+        .collect();
+
+    // The highest priority channel is dispatched by the first dispatcher, and so on; the priority 0
+    // channel is run by `idle`
+    let dispatchers = channels
+        .keys()
+        .rev()
+        .filter(|&&priority| priority != 0)
+        .zip(app.args.extern_interrupts.keys())
+        .collect::<BTreeMap<_, _>>();
+
+    // The priorities assigned from deadlines, as reported to the user
+    let assigned_priorities = optimize::auto_priority_tasks(app, settings)
+        .filter(|task| contexts.iter().any(|context| context.name() == task.name()))
+        .map(|task| (task.name().clone(), task.priority()))
+        .collect();
+
+    let tasks = contexts
+        .iter()
+        .map(|context| {
+            let (binds, channel, message) = match context {
+                TaskRef::HardwareTask(_, task) => (Some(task.args.binds.clone()), None, vec![]),
+                TaskRef::SoftwareTask(_, task) => (
+                    None,
+                    Some(task.args.priority),
+                    task.inputs.iter().map(|input| input.ty.clone()).collect(),
+                ),
+                TaskRef::Init(_) | TaskRef::Idle(_) => (None, None, vec![]),
+            };
+
+            TaskInfo {
+                name: context.name().clone(),
+                kind: context.kind(),
+                priority: context.priority(),
+                binds,
+                channel,
+                dispatcher: channel
+                    .and_then(|priority| dispatchers.get(&priority))
+                    .map(|&dispatcher| dispatcher.clone()),
+                message,
+            }
+        })
+        .collect();
+
     Ok(Analysis {
         channels,
+        message_channels: message_channel_infos,
+        timer_queues,
+        periodic_tasks,
+        queues,
         shared_resource_locations,
         local_resource_locations,
         tasks,
+        assigned_priorities,
         ownerships,
+        field_ownerships,
+        critical_sections,
+        locks,
+        field_locks,
         send_types,
         sync_types,
     })
 }
 
+// Accounts for an access at `priority` to the resource `name`; returns whether the resource is now
+// known to be accessed at different priorities
+fn own<K>(ownerships: &mut IndexMap<K, Ownership>, name: K, priority: Priority) -> bool
+where
+    K: Eq + Hash,
+{
+    let ownership = match ownerships.entry(name) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => {
+            entry.insert(Ownership::Owned { priority });
+            return false;
+        }
+    };
+
+    match *ownership {
+        Ownership::Owned { priority: ceiling }
+        | Ownership::CoOwned { priority: ceiling }
+        | Ownership::Contended { ceiling }
+            if priority != ceiling =>
+        {
+            *ownership = Ownership::Contended {
+                ceiling: cmp::max(ceiling, priority),
+            };
+
+            true
+        }
+
+        Ownership::Owned { priority: ceil } if ceil == priority => {
+            *ownership = Ownership::CoOwned { priority };
+
+            false
+        }
+
+        _ => false,
+    }
+}
+
+// The accesses of `context` to the fields of the resources in `enabled`
+fn enabled_shared_fields<'a>(
+    context: TaskRef<'a>,
+    enabled: &'a Map<&SharedResource>,
+) -> impl Iterator<Item = (&'a FieldPath, &'a Access)> + 'a {
+    context
+        .shared_fields()
+        .filter(move |(path, _)| enabled.contains_key(&path.resource))
+}
+
+// The accesses of `context` to the resources in `enabled`
+fn enabled_shared_resources<'a>(
+    context: TaskRef<'a>,
+    enabled: &'a Map<&SharedResource>,
+) -> impl Iterator<Item = (&'a Ident, &'a Access)> + 'a {
+    context
+        .shared_resources()
+        .filter(move |(name, _)| enabled.contains_key(*name))
+}
+
 /// Priority ceiling
 pub type Ceiling = Option<u8>;
 
@@ -302,14 +778,30 @@ pub type Resource = Ident;
 /// Task name
 pub type Task = Ident;
 
-/// List of tasks names
-pub type Tasks = Vec<Ident>;
+/// List of all the tasks
+pub type Tasks = Vec<TaskInfo>;
 
 /// The result of analyzing an RTIC application
 pub struct Analysis {
     /// SPSC message channels
+    ///
+    /// The priority 0 channel, if any, is run by `idle` and needs no dispatcher
     pub channels: Channels,
 
+    /// The `#[channel]`s used by at least one task, keyed by name
+    pub message_channels: MessageChannels,
+
+    /// The timer queue of every monotonic, keyed by monotonic name
+    pub timer_queues: TimerQueues,
+
+    /// Periodic software tasks; backends start them from `init` and reschedule them after every run
+    pub periodic_tasks: PeriodicTasks,
+
+    /// The ready queues of the channels and the timer queues, analyzed as resources
+    ///
+    /// Their ownerships are computed like those of `#[shared]` resources
+    pub queues: Queues,
+
     /// Location of all *used* shared resources
     ///
     /// If a resource is not listed here it means that's a "dead" (never accessed) resource and the
@@ -322,12 +814,40 @@ pub struct Analysis {
     /// backend should not generate code for it
     pub local_resource_locations: LocalResourceLocations,
 
-    /// A vector containing all task names
+    /// `init`, `idle` (if any), the hardware tasks and then the software tasks, in declaration
+    /// order (the same order as [`App::contexts`])
+    ///
+    /// Tasks disabled by [`Settings::cfgs`] are left out of this and all other fields
     pub tasks: Tasks,
 
+    /// The tasks whose priority was assigned from their deadline (`priority = auto` or
+    /// `#[app(priorities = "deadline-monotonic")]`) and their final priority
+    ///
+    /// Report these so that users can pin them with `priority = ..`
+    pub assigned_priorities: AssignedPriorities,
+
     /// Resource ownership
     pub ownerships: Ownerships,
 
+    /// Ownership of the fields that are locked separately from the rest of their resource, e.g.
+    /// `shared = [state.counter]`
+    pub field_ownerships: FieldOwnerships,
+
+    /// Shared resources whose ceiling, or the ceiling of one of their fields, can only be enforced
+    /// by masking all interrupts
+    ///
+    /// See [`Lock::critical_section`] for each access
+    pub critical_sections: CriticalSections,
+
+    /// How each context accesses its shared resources, keyed by context and then by resource
+    ///
+    /// Contexts are listed in [`App::contexts`] order; those without shared resources map to an
+    /// empty table
+    pub locks: Locks,
+
+    /// Like `locks` but for the field accesses, keyed by context and then by field path
+    pub field_locks: FieldLocks,
+
     /// These types must implement the `Send` trait
     pub send_types: SendTypes,
 
@@ -338,20 +858,99 @@ pub struct Analysis {
 /// All channels, keyed by dispatch priority
 pub type Channels = BTreeMap<Priority, Channel>;
 
+/// Message channels declared with `#[channel]`, keyed by name
+pub type MessageChannels = IndexMap<Ident, MessageChannelInfo>;
+
+/// All timer queues, keyed by monotonic
+pub type TimerQueues = IndexMap<Ident, TimerQueue>;
+
+/// Periodic tasks
+pub type PeriodicTasks = IndexMap<Task, PeriodicTask>;
+
+/// Internal queues analyzed as resources
+pub type Queues = IndexMap<Queue, QueueResource>;
+
 /// Location of all *used* shared resources
 pub type SharedResourceLocations = IndexMap<Resource, Location>;
 
 /// Location of all *used* local resources
 pub type LocalResourceLocations = IndexMap<Resource, Location>;
 
+/// Priorities assigned from deadlines, keyed by task
+pub type AssignedPriorities = IndexMap<Task, Priority>;
+
 /// Resource ownership
 pub type Ownerships = IndexMap<Resource, Ownership>;
 
-/// These types must implement the `Send` trait
-pub type SendTypes = Set<Box<Type>>;
+/// Field ownership
+pub type FieldOwnerships = IndexMap<FieldPath, Ownership>;
+
+/// Shared resources that must be locked using a global critical section
+pub type CriticalSections = Set<Resource>;
+
+/// Shared resource accesses of every context
+pub type Locks = IndexMap<Task, IndexMap<Resource, Lock>>;
+
+/// Field accesses of every context
+pub type FieldLocks = IndexMap<Task, IndexMap<FieldPath, Lock>>;
+
+/// These types must implement the `Send` trait, with the reasons why
+///
+/// Types written differently but with the same canonical form are listed once
+pub type SendTypes = IndexMap<NormalizedType, Vec<SendReason>>;
 
 /// These types must implement the `Sync` trait
-pub type SyncTypes = Set<Box<Type>>;
+///
+/// Types written differently but with the same canonical form are listed once
+pub type SyncTypes = Set<NormalizedType>;
+
+/// Why a type must implement the `Send` trait: a value of the type moves between contexts that run
+/// at different priorities
+///
+/// Contexts are listed with their priorities; `init` runs at priority 0
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum SendReason {
+    /// The `#[shared]` resource is initialized by `init` and used by `users`, some of which run at
+    /// a nonzero priority
+    SharedResource {
+        /// The resource
+        resource: Resource,
+        /// The contexts that access the resource
+        users: Vec<(Task, Priority)>,
+    },
+
+    /// The `#[local]` resource is initialized by `init` and moved to `users`, some of which run at
+    /// a nonzero priority
+    LocalResource {
+        /// The resource
+        resource: Resource,
+        /// The contexts that access the resource
+        users: Vec<(Task, Priority)>,
+    },
+
+    /// The messages of the `#[channel]` are sent by tasks that run at a priority other than the
+    /// receiver's
+    MessageChannel {
+        /// The channel
+        channel: Ident,
+        /// The tasks that send messages
+        senders: Vec<(Task, Priority)>,
+        /// The task that receives the messages
+        receiver: (Task, Priority),
+    },
+
+    /// The input of the software task is sent by contexts that run at other priorities
+    TaskInput {
+        /// The software task
+        task: Task,
+        /// Priority of the software task
+        priority: Priority,
+        /// The contexts that spawn the task (`spawn = [..]`); `None` if no context declares it, in
+        /// which case it may be spawned from anywhere
+        spawners: Option<Vec<(Task, Priority)>>,
+    },
+}
 
 /// A channel used to send messages
 #[derive(Debug, Default)]
@@ -363,6 +962,164 @@ pub struct Channel {
     pub tasks: BTreeSet<Task>,
 }
 
+/// A message channel declared with `#[channel]` and the tasks that use it
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct MessageChannelInfo {
+    /// The type of the messages; it must be `Send` if a sender runs at a priority other than the
+    /// receiver's
+    pub ty: Box<Type>,
+
+    /// The maximum number of messages the channel can hold
+    pub capacity: u8,
+
+    /// The tasks that send messages (`send = [..]`) and their priorities
+    pub senders: Vec<(Task, Priority)>,
+
+    /// The task that receives the messages (`recv = [..]`) and its priority
+    pub receiver: (Task, Priority),
+
+    /// The ownership of the channel, computed from the priorities of its senders and receiver
+    ///
+    /// Use [`Ownership::needs_lock`] with the priority of an endpoint
+    pub ownership: Ownership,
+}
+
+/// A task of the application
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct TaskInfo {
+    /// The task name, as written in the source code
+    pub name: Task,
+
+    /// Whether this is `init`, `idle`, a hardware task or a software task
+    pub kind: TaskKind,
+
+    /// Priority of the task, after optimizations
+    pub priority: Priority,
+
+    /// The interrupt or exception a hardware task is bound to
+    pub binds: Option<Ident>,
+
+    /// The channel (see [`Analysis::channels`]) a software task is spawned on
+    pub channel: Option<Priority>,
+
+    /// The dispatcher interrupt that runs the channel of a software task
+    ///
+    /// `None` if there are fewer dispatchers than channels and for priority 0 tasks, which are run
+    /// by `idle`
+    pub dispatcher: Option<Ident>,
+
+    /// The types of the inputs of a software task, in order; a message is a tuple of these
+    ///
+    /// Empty for software tasks without inputs and for the other contexts
+    pub message: Vec<Box<Type>>,
+}
+
+/// How a context accesses one of its shared resources
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Lock {
+    /// The access mode declared in the task attribute
+    pub access: Access,
+
+    /// Priority of the context
+    pub priority: Priority,
+
+    /// Priority ceiling of the resource; equal to `priority` if the resource is not contended
+    pub ceiling: u8,
+
+    /// Whether the context must lock the resource to access it (see [`Ownership::needs_lock`])
+    pub needs_lock: bool,
+
+    /// Whether the lock must be a global critical section (see
+    /// [`Analysis::critical_sections`])
+    pub critical_section: bool,
+
+    /// Whether the resource is declared `#[lock_free]`
+    pub lock_free: bool,
+
+    /// Whether the context can only read the resource (`&x`)
+    pub read_only: bool,
+}
+
+/// The queue of the messages scheduled on a monotonic
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct TimerQueue {
+    /// The queue capacity: the sum of the capacities of `tasks`
+    pub capacity: u16,
+
+    /// Priority of the monotonic handler, which dequeues the messages
+    ///
+    /// The highest priority of `tasks` unless given with `#[monotonic(priority = ..)]`
+    pub priority: Priority,
+
+    /// Tasks that can be scheduled on this monotonic
+    pub tasks: BTreeSet<Task>,
+}
+
+/// A task that runs periodically: `#[task(period = ..)]`
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct PeriodicTask {
+    /// The monotonic the task is scheduled on: the one given with `monotonic = ..` or else the
+    /// default monotonic
+    pub monotonic: Ident,
+
+    /// Time between runs, in the duration type of the monotonic
+    pub period: Box<Expr>,
+
+    /// Delay before the first run
+    pub offset: Option<Box<Expr>>,
+
+    /// `period` in ticks of the monotonic; known if it's a literal like `10.millis()` and the
+    /// monotonic has a `tick_hz`
+    pub period_ticks: Option<u64>,
+
+    /// `offset` in ticks of the monotonic; known under the same conditions as `period_ticks`
+    pub offset_ticks: Option<u64>,
+}
+
+/// An internal queue
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Queue {
+    /// The ready queue of the channel with this dispatch priority
+    Ready(Priority),
+
+    /// The timer queue of this monotonic
+    Timer(Ident),
+}
+
+/// A context that uses an internal queue
+#[derive(Clone, Debug, PartialEq)]
+pub enum QueueUser {
+    /// A context that can spawn or schedule tasks, which enqueues messages
+    ///
+    /// A context spawns the tasks it lists in `spawn = [..]`, and every task that no context lists;
+    /// `init` is never a spawner because it runs with interrupts disabled
+    Spawner(Task),
+
+    /// The dispatcher of the channel, which dequeues messages from the ready queue; `idle` for the
+    /// priority 0 channel
+    Dispatcher,
+
+    /// The handler of this monotonic, which moves due messages from the timer queue to the ready
+    /// queues
+    Monotonic(Ident),
+}
+
+/// An internal queue analyzed as a resource
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct QueueResource {
+    /// The contexts that use the queue and their priorities
+    pub users: Vec<(QueueUser, Priority)>,
+
+    /// The ownership of the queue; use [`Ownership::needs_lock`] with the priority of a user
+    pub ownership: Ownership,
+}
+
 /// Resource ownership
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Ownership {
@@ -387,6 +1144,10 @@ pub enum Ownership {
 
 impl Ownership {
     /// Whether this resource needs to a lock at this priority level
+    ///
+    /// Priorities are logical so this holds for every target; see
+    /// [`Profile::needs_global_critical_section`](crate::target::Profile::needs_global_critical_section)
+    /// for the kind of lock that's required
     pub fn needs_lock(&self, priority: u8) -> bool {
         match self {
             Ownership::Owned { .. } | Ownership::CoOwned { .. } => false,
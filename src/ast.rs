@@ -1,8 +1,16 @@
 //! Abstract Syntax Tree
 
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use indexmap::IndexMap;
+use proc_macro2::{Delimiter, Span, TokenStream as TokenStream2, TokenTree};
+use quote::ToTokens;
 use syn::{Attribute, Expr, Ident, Item, ItemUse, Pat, PatType, Path, Stmt, Type};
 
-use crate::Map;
+use crate::{Map, Set};
 
 /// The `#[app]` attribute
 #[derive(Debug)]
@@ -23,6 +31,9 @@ pub struct App {
     /// Monotonic clocks
     pub monotonics: Map<Monotonic>,
 
+    /// Message channels between tasks: `#[channel]`
+    pub message_channels: Map<MessageChannel>,
+
     /// Resources shared between tasks defined in `#[shared]`
     pub shared_resources: Map<SharedResource>,
 
@@ -64,6 +75,45 @@ pub struct AppArgs {
 
     /// Interrupts used to dispatch software tasks
     pub extern_interrupts: ExternInterrupts,
+
+    /// How the priorities of tasks without a `priority` argument are chosen
+    pub priorities: PriorityAssignment,
+}
+
+impl Default for AppArgs {
+    fn default() -> Self {
+        Self {
+            device: None,
+            peripherals: true,
+            extern_interrupts: ExternInterrupts::new(),
+            priorities: PriorityAssignment::Manual,
+        }
+    }
+}
+
+/// How the priorities of tasks without a `priority` argument are chosen:
+/// `#[app(priorities = "..")]`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PriorityAssignment {
+    /// They run at priority 1: `"manual"`, the default
+    Manual,
+
+    /// They are assigned a priority from their deadline, like `priority = auto` tasks:
+    /// `"deadline-monotonic"`
+    DeadlineMonotonic,
+}
+
+/// How the `priority` argument of a task was given
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PriorityArg {
+    /// No `priority` argument
+    Omitted,
+
+    /// `priority = 3`
+    Explicit,
+
+    /// `priority = auto`: assigned from the deadline of the task, or its period
+    Auto,
 }
 
 /// The `init`-ialization function
@@ -98,12 +148,18 @@ pub struct Init {
 pub struct InitArgs {
     /// Local resources that can be accessed from this context
     pub local_resources: LocalResources,
+
+    /// Software tasks this context spawns: `spawn = [foo]`
+    ///
+    /// A task that appears in any `spawn` list can only be spawned from the contexts that list it
+    pub spawn: Set<Ident>,
 }
 
 impl Default for InitArgs {
     fn default() -> Self {
         Self {
             local_resources: LocalResources::new(),
+            spawn: Set::new(),
         }
     }
 }
@@ -128,6 +184,35 @@ pub struct Idle {
     pub stmts: Vec<Stmt>,
 }
 
+impl Idle {
+    /// Whether this function yields to the priority 0 tasks, i.e. whether it calls `yield_now()`
+    /// (e.g. `rtic::yield_now()` or `cx.yield_now()`)
+    pub fn yields(&self) -> bool {
+        fn calls_yield_now(tokens: TokenStream2) -> bool {
+            let mut tokens = tokens.into_iter().peekable();
+            while let Some(token) = tokens.next() {
+                match token {
+                    TokenTree::Ident(ident) if ident == "yield_now" => {
+                        if let Some(TokenTree::Group(group)) = tokens.peek() {
+                            if group.delimiter() == Delimiter::Parenthesis {
+                                return true;
+                            }
+                        }
+                    }
+                    TokenTree::Group(group) if calls_yield_now(group.stream()) => return true,
+                    _ => {}
+                }
+            }
+
+            false
+        }
+
+        self.stmts
+            .iter()
+            .any(|stmt| calls_yield_now(stmt.to_token_stream()))
+    }
+}
+
 /// `idle` context metadata
 #[derive(Debug)]
 #[non_exhaustive]
@@ -137,6 +222,14 @@ pub struct IdleArgs {
 
     /// Shared resources that can be accessed from this context
     pub shared_resources: SharedResources,
+
+    /// Fields of shared resources that can be accessed from this context
+    pub shared_fields: SharedFields,
+
+    /// Software tasks this context spawns: `spawn = [foo]`
+    ///
+    /// A task that appears in any `spawn` list can only be spawned from the contexts that list it
+    pub spawn: Set<Ident>,
 }
 
 impl Default for IdleArgs {
@@ -144,6 +237,8 @@ impl Default for IdleArgs {
         Self {
             local_resources: LocalResources::new(),
             shared_resources: SharedResources::new(),
+            shared_fields: SharedFields::new(),
+            spawn: Set::new(),
         }
     }
 }
@@ -215,6 +310,37 @@ pub struct MonotonicArgs {
 
     /// If this is the default monotonic
     pub default: bool,
+
+    /// The tick rate of the timer, in Hz: `tick_hz = 1_000_000`
+    pub tick_hz: Option<u32>,
+
+    /// The width of the timer counter: `bits = 32`
+    pub bits: Option<u8>,
+}
+
+/// A message channel between tasks: `#[channel(capacity = 4)] type Ch = T;`
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct MessageChannel {
+    /// `#[cfg]` attributes like `#[cfg(debug_assertions)]`
+    pub cfgs: Vec<Attribute>,
+
+    /// The identifier of the channel
+    pub ident: Ident,
+
+    /// The type of the messages
+    pub ty: Box<Type>,
+
+    /// Channel args
+    pub args: MessageChannelArgs,
+}
+
+/// Message channel metadata
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct MessageChannelArgs {
+    /// The maximum number of messages the channel can hold
+    pub capacity: u8,
 }
 
 /// A software task
@@ -243,6 +369,17 @@ pub struct SoftwareTask {
     pub is_extern: bool,
 }
 
+impl SoftwareTask {
+    /// Whether this task can be scheduled on the monotonic called `monotonic`
+    pub fn can_be_scheduled_on(&self, monotonic: &Ident) -> bool {
+        self.args
+            .monotonic
+            .as_ref()
+            .map(|name| name == monotonic)
+            .unwrap_or(true)
+    }
+}
+
 /// Software task metadata
 #[derive(Debug)]
 #[non_exhaustive]
@@ -250,23 +387,68 @@ pub struct SoftwareTaskArgs {
     /// The task capacity: the maximum number of pending messages that can be queued
     pub capacity: u8,
 
+    /// The only monotonic this task can be scheduled on: `monotonic = Mono`
+    ///
+    /// When `None`, the task can be scheduled on every monotonic
+    pub monotonic: Option<Ident>,
+
+    /// The period of a periodic task: `period = 10.millis()`
+    ///
+    /// Periodic tasks are started by the framework and rescheduled every period on their
+    /// monotonic
+    pub period: Option<Box<Expr>>,
+
+    /// The delay before the first run of a periodic task: `offset = 1.millis()`
+    pub offset: Option<Box<Expr>>,
+
     /// The priority of this task
+    ///
+    /// Priority 0 tasks run cooperatively inside `idle`, whenever it yields (see [`Idle::yields`])
     pub priority: u8,
 
+    /// How `priority` was given
+    pub priority_arg: PriorityArg,
+
+    /// The relative deadline of this task: `deadline = 5.millis()`
+    pub deadline: Option<Box<Expr>>,
+
     /// Local resources that can be accessed from this context
     pub local_resources: LocalResources,
 
     /// Shared resources that can be accessed from this context
     pub shared_resources: SharedResources,
+
+    /// Fields of shared resources that can be accessed from this context
+    pub shared_fields: SharedFields,
+
+    /// Message channels this task sends to: `send = [Ch]`
+    pub send: Set<Ident>,
+
+    /// Message channels this task receives from: `recv = [Ch]`
+    pub recv: Set<Ident>,
+
+    /// Software tasks this context spawns: `spawn = [foo]`
+    ///
+    /// A task that appears in any `spawn` list can only be spawned from the contexts that list it
+    pub spawn: Set<Ident>,
 }
 
 impl Default for SoftwareTaskArgs {
     fn default() -> Self {
         Self {
             capacity: 1,
+            monotonic: None,
+            period: None,
+            offset: None,
             priority: 1,
+            priority_arg: PriorityArg::Omitted,
+            deadline: None,
             local_resources: LocalResources::new(),
             shared_resources: SharedResources::new(),
+            shared_fields: SharedFields::new(),
+            send: Set::new(),
+            recv: Set::new(),
+            spawn: Set::new(),
         }
     }
 }
@@ -304,11 +486,31 @@ pub struct HardwareTaskArgs {
     /// The priority of this task
     pub priority: u8,
 
+    /// How `priority` was given
+    pub priority_arg: PriorityArg,
+
+    /// The relative deadline of this task: `deadline = 5.millis()`
+    pub deadline: Option<Box<Expr>>,
+
     /// Local resources that can be accessed from this context
     pub local_resources: LocalResources,
 
     /// Shared resources that can be accessed from this context
     pub shared_resources: SharedResources,
+
+    /// Fields of shared resources that can be accessed from this context
+    pub shared_fields: SharedFields,
+
+    /// Message channels this task sends to: `send = [Ch]`
+    pub send: Set<Ident>,
+
+    /// Message channels this task receives from: `recv = [Ch]`
+    pub recv: Set<Ident>,
+
+    /// Software tasks this context spawns: `spawn = [foo]`
+    ///
+    /// A task that appears in any `spawn` list can only be spawned from the contexts that list it
+    pub spawn: Set<Ident>,
 }
 
 /// A `static mut` variable local to and owned by a context
@@ -363,5 +565,269 @@ impl Access {
 /// Shared resource access list in task attribute
 pub type SharedResources = Map<Access>;
 
+/// Field path access list in task attribute: `shared = [state.counter, &state.config]`
+pub type SharedFields = IndexMap<FieldPath, Access>;
+
+/// A path to a field of a shared resource: `state.config.baud`
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct FieldPath {
+    /// The shared resource: `state`
+    pub resource: Ident,
+
+    /// The fields, outermost first: `config`, `baud`; empty for the whole resource
+    pub fields: Vec<Ident>,
+}
+
+impl FieldPath {
+    /// The path to the whole `resource`
+    pub fn resource(resource: Ident) -> Self {
+        FieldPath {
+            resource,
+            fields: vec![],
+        }
+    }
+
+    /// Whether this path and `other` refer to overlapping data: one of them is a prefix of the
+    /// other
+    pub fn overlaps(&self, other: &FieldPath) -> bool {
+        self.resource == other.resource
+            && self
+                .fields
+                .iter()
+                .zip(&other.fields)
+                .all(|(field, other)| field == other)
+    }
+}
+
+impl fmt::Display for FieldPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.resource)?;
+        for field in &self.fields {
+            write!(f, ".{}", field)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A type as written in the application and its canonical form
+///
+/// The canonical form expands the paths imported by the `use` items of the `#[app]` module and
+/// spells primitives without their `core::primitive` prefix, so `u32`, `core::primitive::u32` and
+/// an imported alias of it are the same type. Equality and hashing only look at the canonical form;
+/// the original type is kept for diagnostics and is the one `ToTokens` emits.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct NormalizedType {
+    /// The type as written by the user
+    pub original: Box<Type>,
+
+    /// The canonical form of the type
+    pub normalized: Box<Type>,
+}
+
+impl PartialEq for NormalizedType {
+    fn eq(&self, other: &Self) -> bool {
+        self.normalized == other.normalized
+    }
+}
+
+impl Eq for NormalizedType {}
+
+impl Hash for NormalizedType {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.normalized.hash(state);
+    }
+}
+
+impl ToTokens for NormalizedType {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        self.original.to_tokens(tokens);
+    }
+}
+
 /// Local resource access/declaration list in task attribute
 pub type LocalResources = Map<TaskLocal>;
+
+/// The kind of an execution context
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TaskKind {
+    /// The `#[init]` function
+    Init,
+
+    /// The `#[idle]` function
+    Idle,
+
+    /// A hardware task: `#[task(binds = ..)]`
+    HardwareTask,
+
+    /// A software task: `#[task]`
+    SoftwareTask,
+}
+
+/// A reference to one of the execution contexts of the application: `init`, `idle` or a task
+#[derive(Clone, Copy, Debug)]
+pub enum TaskRef<'a> {
+    /// The `#[init]` function
+    Init(&'a Init),
+
+    /// The `#[idle]` function
+    Idle(&'a Idle),
+
+    /// A hardware task and its name
+    HardwareTask(&'a Ident, &'a HardwareTask),
+
+    /// A software task and its name
+    SoftwareTask(&'a Ident, &'a SoftwareTask),
+}
+
+impl<'a> TaskRef<'a> {
+    /// The name of this context
+    pub fn name(&self) -> &'a Ident {
+        match *self {
+            TaskRef::Init(init) => &init.name,
+            TaskRef::Idle(idle) => &idle.name,
+            TaskRef::HardwareTask(name, _) | TaskRef::SoftwareTask(name, _) => name,
+        }
+    }
+
+    /// The kind of this context
+    pub fn kind(&self) -> TaskKind {
+        match self {
+            TaskRef::Init(_) => TaskKind::Init,
+            TaskRef::Idle(_) => TaskKind::Idle,
+            TaskRef::HardwareTask(..) => TaskKind::HardwareTask,
+            TaskRef::SoftwareTask(..) => TaskKind::SoftwareTask,
+        }
+    }
+
+    /// The priority this context runs at; `init` and `idle` run at priority 0
+    pub fn priority(&self) -> u8 {
+        match self {
+            TaskRef::Init(_) | TaskRef::Idle(_) => 0,
+            TaskRef::HardwareTask(_, task) => task.args.priority,
+            TaskRef::SoftwareTask(_, task) => task.args.priority,
+        }
+    }
+
+    /// The shared resources this context can access; `init` has none
+    pub fn shared_resources(&self) -> impl Iterator<Item = (&'a Ident, &'a Access)> {
+        let resources = match *self {
+            TaskRef::Init(_) => None,
+            TaskRef::Idle(idle) => Some(&idle.args.shared_resources),
+            TaskRef::HardwareTask(_, task) => Some(&task.args.shared_resources),
+            TaskRef::SoftwareTask(_, task) => Some(&task.args.shared_resources),
+        };
+
+        resources.into_iter().flat_map(|resources| resources.iter())
+    }
+
+    /// The fields of shared resources this context can access; `init` has none
+    pub fn shared_fields(&self) -> impl Iterator<Item = (&'a FieldPath, &'a Access)> {
+        let fields = match *self {
+            TaskRef::Init(_) => None,
+            TaskRef::Idle(idle) => Some(&idle.args.shared_fields),
+            TaskRef::HardwareTask(_, task) => Some(&task.args.shared_fields),
+            TaskRef::SoftwareTask(_, task) => Some(&task.args.shared_fields),
+        };
+
+        fields.into_iter().flat_map(|fields| fields.iter())
+    }
+
+    /// The local resources this context can access or declares
+    pub fn local_resources(&self) -> &'a LocalResources {
+        match *self {
+            TaskRef::Init(init) => &init.args.local_resources,
+            TaskRef::Idle(idle) => &idle.args.local_resources,
+            TaskRef::HardwareTask(_, task) => &task.args.local_resources,
+            TaskRef::SoftwareTask(_, task) => &task.args.local_resources,
+        }
+    }
+
+    /// How the priority of this context was given; `None` for `init` and `idle`
+    pub fn priority_arg(&self) -> Option<PriorityArg> {
+        match self {
+            TaskRef::Init(_) | TaskRef::Idle(_) => None,
+            TaskRef::HardwareTask(_, task) => Some(task.args.priority_arg),
+            TaskRef::SoftwareTask(_, task) => Some(task.args.priority_arg),
+        }
+    }
+
+    /// The deadline of this context: its `deadline` or else its `period`
+    pub fn deadline(&self) -> Option<&'a Expr> {
+        match *self {
+            TaskRef::Init(_) | TaskRef::Idle(_) => None,
+            TaskRef::HardwareTask(_, task) => task.args.deadline.as_deref(),
+            TaskRef::SoftwareTask(_, task) => task
+                .args
+                .deadline
+                .as_deref()
+                .or(task.args.period.as_deref()),
+        }
+    }
+
+    /// The message channels this context sends to; only tasks can use channels
+    pub fn send_channels(&self) -> impl Iterator<Item = &'a Ident> {
+        let channels = match *self {
+            TaskRef::Init(_) | TaskRef::Idle(_) => None,
+            TaskRef::HardwareTask(_, task) => Some(&task.args.send),
+            TaskRef::SoftwareTask(_, task) => Some(&task.args.send),
+        };
+
+        channels.into_iter().flat_map(|channels| channels.iter())
+    }
+
+    /// The message channels this context receives from; only tasks can use channels
+    pub fn recv_channels(&self) -> impl Iterator<Item = &'a Ident> {
+        let channels = match *self {
+            TaskRef::Init(_) | TaskRef::Idle(_) => None,
+            TaskRef::HardwareTask(_, task) => Some(&task.args.recv),
+            TaskRef::SoftwareTask(_, task) => Some(&task.args.recv),
+        };
+
+        channels.into_iter().flat_map(|channels| channels.iter())
+    }
+
+    /// The software tasks this context declares it spawns: `spawn = [..]`
+    pub fn spawned_tasks(&self) -> impl Iterator<Item = &'a Ident> {
+        match *self {
+            TaskRef::Init(init) => init.args.spawn.iter(),
+            TaskRef::Idle(idle) => idle.args.spawn.iter(),
+            TaskRef::HardwareTask(_, task) => task.args.spawn.iter(),
+            TaskRef::SoftwareTask(_, task) => task.args.spawn.iter(),
+        }
+    }
+
+    /// `#[cfg]` attributes of this context; `init` and `idle` can't be `#[cfg]`-ed
+    pub fn cfgs(&self) -> &'a [Attribute] {
+        match *self {
+            TaskRef::Init(_) | TaskRef::Idle(_) => &[],
+            TaskRef::HardwareTask(_, task) => &task.cfgs,
+            TaskRef::SoftwareTask(_, task) => &task.cfgs,
+        }
+    }
+
+    /// Attributes that will apply to the function of this context
+    pub fn attrs(&self) -> &'a [Attribute] {
+        match *self {
+            TaskRef::Init(init) => &init.attrs,
+            TaskRef::Idle(idle) => &idle.attrs,
+            TaskRef::HardwareTask(_, task) => &task.attrs,
+            TaskRef::SoftwareTask(_, task) => &task.attrs,
+        }
+    }
+
+    /// Whether this context is declared externally, in an `extern` block
+    pub fn is_extern(&self) -> bool {
+        match self {
+            TaskRef::Init(_) | TaskRef::Idle(_) => false,
+            TaskRef::HardwareTask(_, task) => task.is_extern,
+            TaskRef::SoftwareTask(_, task) => task.is_extern,
+        }
+    }
+
+    /// The span of the name of this context
+    pub fn span(&self) -> Span {
+        self.name().span()
+    }
+}
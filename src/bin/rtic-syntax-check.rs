@@ -0,0 +1,229 @@
+//! Checks RTIC applications without compiling them
+//!
+//! Usage: `rtic-syntax-check [OPTIONS] FILE..`
+//!
+//! Exits with status 0 if every application is valid, 1 if any of them has errors and 2 on usage
+//! or I/O errors.
+
+#![deny(rust_2018_compatibility)]
+#![deny(rust_2018_idioms)]
+#![deny(warnings)]
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use rtic_syntax::{ast::App, cfg::CfgSet, target::Target, Settings};
+
+const USAGE: &str = "\
+Usage: rtic-syntax-check [OPTIONS] FILE..
+
+Checks the `#[app]` module of each FILE
+
+Options:
+    --parse-binds               accept the `binds` argument in `#[task]`
+    --parse-extern-interrupt    accept `extern` interrupts
+    --optimize-priorities       compress priorities
+    --target TARGET             generic, cortex-m, riscv-clic, riscv-plic or host
+    --svd FILE                  check interrupts against the device described by FILE
+    --cfg SPEC                  enable the `cfg` option SPEC, e.g. `feature=\"foo\"`; if never given,
+                                every `#[cfg]` is assumed to hold
+    --app-attribute PATH        the attribute of the application module (default: any `app`)
+    -q, --quiet                 don't print a report for valid applications
+    -h, --help                  print this message";
+
+struct Options {
+    files: Vec<PathBuf>,
+    quiet: bool,
+    parse_binds: bool,
+    parse_extern_interrupt: bool,
+    optimize_priorities: bool,
+    target: Target,
+    svd: Option<PathBuf>,
+    app_attribute: Option<syn::Path>,
+    cfgs: Option<CfgSet>,
+}
+
+impl Options {
+    fn settings(&self) -> Settings {
+        let mut settings = Settings::default();
+        settings.parse_binds = self.parse_binds;
+        settings.parse_extern_interrupt = self.parse_extern_interrupt;
+        settings.optimize_priorities = self.optimize_priorities;
+        settings.target = self.target;
+        settings.svd = self.svd.clone();
+        settings.app_attribute = self.app_attribute.clone();
+        settings.cfgs = self.cfgs.clone();
+        settings
+    }
+}
+
+fn main() {
+    let options = match options(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let mut valid = true;
+    for path in &options.files {
+        match check(path, &options) {
+            Ok(ok) => valid &= ok,
+            Err(e) => {
+                eprintln!("error: {}: {}", path.display(), e);
+                process::exit(2);
+            }
+        }
+    }
+
+    process::exit(if valid { 0 } else { 1 });
+}
+
+fn options(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        files: vec![],
+        quiet: false,
+        parse_binds: false,
+        parse_extern_interrupt: false,
+        optimize_priorities: false,
+        target: Target::Generic,
+        svd: None,
+        app_attribute: None,
+        cfgs: None,
+    };
+
+    while let Some(arg) = args.next() {
+        match &*arg {
+            "--parse-binds" => options.parse_binds = true,
+            "--parse-extern-interrupt" => options.parse_extern_interrupt = true,
+            "--optimize-priorities" => options.optimize_priorities = true,
+            "-q" | "--quiet" => options.quiet = true,
+            "--target" => {
+                options.target = match args.next().as_deref() {
+                    Some("generic") => Target::Generic,
+                    Some("cortex-m") => Target::CortexM,
+                    Some("riscv-clic") => Target::RiscvClic,
+                    Some("riscv-plic") => Target::RiscvPlic,
+                    Some("host") => Target::Host,
+                    Some(target) => return Err(format!("unknown target `{}`", target)),
+                    None => return Err("`--target` expects a value".into()),
+                }
+            }
+            "--svd" => match args.next() {
+                Some(svd) => options.svd = Some(svd.into()),
+                None => return Err("`--svd` expects a value".into()),
+            },
+            "--cfg" => match args.next() {
+                Some(spec) => {
+                    let cfgs = options.cfgs.get_or_insert_with(CfgSet::new);
+                    match spec.split_once('=') {
+                        Some((key, value)) => {
+                            let value = value.trim().trim_matches('"');
+                            cfgs.enable_value(key.trim(), value)
+                        }
+                        None => cfgs.enable(spec.trim()),
+                    };
+                }
+                None => return Err("`--cfg` expects a value".into()),
+            },
+            "--app-attribute" => match args.next() {
+                Some(path) => match syn::parse_str(&path) {
+                    Ok(path) => options.app_attribute = Some(path),
+                    Err(_) => return Err(format!("`{}` is not a valid path", path)),
+                },
+                None => return Err("`--app-attribute` expects a value".into()),
+            },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{}`", arg)),
+            _ => options.files.push(arg.into()),
+        }
+    }
+
+    if options.files.is_empty() {
+        return Err("no input files".into());
+    }
+
+    Ok(options)
+}
+
+// Returns whether the application is valid; I/O errors are reported as `Err`
+fn check(path: &Path, options: &Options) -> Result<bool, String> {
+    let source = fs::read_to_string(path).map_err(|e| e.to_string())?;
+
+    let file = match syn::parse_file(&source) {
+        Ok(file) => file,
+        Err(e) => {
+            report_errors(path, vec![e]);
+            return Ok(false);
+        }
+    };
+
+    let settings = options.settings();
+    let (args, module) = match rtic_syntax::find_app(&file, &settings) {
+        Ok(app) => app,
+        Err(e) => {
+            report_errors(path, vec![e]);
+            return Ok(false);
+        }
+    };
+    let (app, analysis, errors) = rtic_syntax::parse_recovering(args, module, settings);
+
+    if !errors.is_empty() {
+        report_errors(path, errors);
+        return Ok(false);
+    }
+
+    if !options.quiet {
+        if let Some(app) = app {
+            report(path, &app);
+        }
+
+        // Automatic priorities may change as tasks are added; tell users how to pin them
+        if let Some(analysis) = analysis {
+            for (task, priority) in &analysis.assigned_priorities {
+                println!(
+                    "{}: note: task `{}` was assigned priority {}; pin it with `priority = {}`",
+                    path.display(),
+                    task,
+                    priority,
+                    priority
+                );
+            }
+        }
+    }
+
+    Ok(true)
+}
+
+fn report_errors(path: &Path, errors: Vec<syn::parse::Error>) {
+    for error in errors {
+        let start = error.span().start();
+        eprintln!(
+            "{}:{}:{}: error: {}",
+            path.display(),
+            start.line,
+            start.column + 1,
+            error
+        );
+    }
+}
+
+fn report(path: &Path, app: &App) {
+    println!(
+        "{}: ok: app `{}` with {} hardware task(s), {} software task(s), {} monotonic(s), {} shared and {} local resource(s)",
+        path.display(),
+        app.name,
+        app.hardware_tasks.len(),
+        app.software_tasks.len(),
+        app.monotonics.len(),
+        app.shared_resources.len(),
+        app.local_resources.len(),
+    );
+}
@@ -0,0 +1,239 @@
+//! Evaluation of `#[cfg]` attributes
+
+use std::collections::BTreeSet;
+
+use syn::{parse, spanned::Spanned, Attribute, Lit, Meta, NestedMeta};
+
+/// A set of active `cfg` options, e.g. `debug_assertions` or `feature = "foo"`
+#[derive(Clone, Debug, Default)]
+pub struct CfgSet {
+    options: BTreeSet<(String, Option<String>)>,
+}
+
+impl CfgSet {
+    /// Creates an empty set: every option is disabled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enables the option `name`, e.g. `debug_assertions`
+    pub fn enable(&mut self, name: &str) -> &mut Self {
+        self.options.insert((name.to_string(), None));
+        self
+    }
+
+    /// Enables the option `key = "value"`, e.g. `feature = "foo"`
+    pub fn enable_value(&mut self, key: &str, value: &str) -> &mut Self {
+        self.options
+            .insert((key.to_string(), Some(value.to_string())));
+        self
+    }
+
+    /// Whether the option `name` (`value` is `None`) or `name = "value"` is enabled
+    pub fn is_enabled(&self, name: &str, value: Option<&str>) -> bool {
+        self.options
+            .contains(&(name.to_string(), value.map(|value| value.to_string())))
+    }
+}
+
+// Whether all the `#[cfg]` attributes `cfgs` hold; always `true` if `set` is `None`
+pub(crate) fn enabled(cfgs: &[Attribute], set: Option<&CfgSet>) -> parse::Result<bool> {
+    let set = match set {
+        Some(set) => set,
+        None => return Ok(true),
+    };
+
+    let mut enabled = true;
+    for cfg in cfgs {
+        enabled &= eval(&predicate(cfg)?, set)?;
+    }
+
+    Ok(enabled)
+}
+
+fn eval(predicate: &NestedMeta, set: &CfgSet) -> parse::Result<bool> {
+    let meta = match predicate {
+        NestedMeta::Meta(meta) => meta,
+        NestedMeta::Lit(_) => return Err(unevaluable(predicate)),
+    };
+
+    let name = match meta.path().get_ident() {
+        Some(name) => name.to_string(),
+        None => return Err(unevaluable(meta)),
+    };
+
+    match meta {
+        Meta::Path(_) => Ok(set.is_enabled(&name, None)),
+
+        Meta::NameValue(nv) => match &nv.lit {
+            Lit::Str(value) => Ok(set.is_enabled(&name, Some(&value.value()))),
+            _ => Err(unevaluable(meta)),
+        },
+
+        Meta::List(list) => {
+            // evaluate every operand so that all unevaluable predicates are reported
+            let operands = list
+                .nested
+                .iter()
+                .map(|operand| eval(operand, set))
+                .collect::<parse::Result<Vec<_>>>()?;
+
+            match &*name {
+                "all" => Ok(operands.iter().all(|operand| *operand)),
+                "any" => Ok(operands.iter().any(|operand| *operand)),
+                "not" if operands.len() == 1 => Ok(!operands[0]),
+                _ => Err(unevaluable(meta)),
+            }
+        }
+    }
+}
+
+// Whether `premises` holding implies that `conclusions` hold, using simple syntactic rules: a
+// conclusion must appear among the premises (conjunctions are split), all of the operands of an
+// `all` must be implied and one of the operands of an `any` must be implied
+pub(crate) fn implies(premises: &[Attribute], conclusions: &[Attribute]) -> parse::Result<bool> {
+    fn conjuncts(predicate: NestedMeta, facts: &mut Vec<NestedMeta>) {
+        match predicate {
+            NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("all") => {
+                for operand in list.nested {
+                    conjuncts(operand, facts);
+                }
+            }
+            predicate => facts.push(predicate),
+        }
+    }
+
+    fn implied(premises: &[NestedMeta], conclusion: &NestedMeta) -> bool {
+        if premises.contains(conclusion) {
+            return true;
+        }
+
+        match conclusion {
+            NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("all") => {
+                list.nested.iter().all(|operand| implied(premises, operand))
+            }
+            NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("any") => {
+                list.nested.iter().any(|operand| implied(premises, operand))
+            }
+            _ => false,
+        }
+    }
+
+    let mut facts = vec![];
+    for premise in premises {
+        conjuncts(predicate(premise)?, &mut facts);
+    }
+
+    for conclusion in conclusions {
+        if !implied(&facts, &predicate(conclusion)?) {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+// The predicate of the `#[cfg]` attribute `cfg`
+fn predicate(cfg: &Attribute) -> parse::Result<NestedMeta> {
+    match cfg.parse_meta()? {
+        Meta::List(list) if list.nested.len() == 1 => Ok(list.nested[0].clone()),
+        meta => Err(unevaluable(&meta)),
+    }
+}
+
+fn unevaluable(predicate: &impl Spanned) -> parse::Error {
+    parse::Error::new(predicate.span(), "this `cfg` predicate can't be evaluated")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CfgSet;
+
+    fn cfgs(predicates: &str) -> Vec<syn::Attribute> {
+        syn::parse_str::<syn::ItemFn>(&format!("{} fn f() {{}}", predicates))
+            .unwrap()
+            .attrs
+    }
+
+    fn enabled(predicate: &str, set: &CfgSet) -> syn::parse::Result<bool> {
+        let item: syn::ItemFn = syn::parse_str(&format!("#[cfg{}] fn f() {{}}", predicate))?;
+        super::enabled(&item.attrs, Some(set))
+    }
+
+    #[test]
+    fn eval() {
+        let mut set = CfgSet::new();
+        set.enable("debug_assertions")
+            .enable_value("feature", "foo");
+
+        for (predicate, expected) in &[
+            ("(debug_assertions)", true),
+            ("(test)", false),
+            ("(feature = \"foo\")", true),
+            ("(feature = \"bar\")", false),
+            ("(not(feature = \"bar\"))", true),
+            ("(all(debug_assertions, feature = \"foo\"))", true),
+            ("(all(test, feature = \"foo\"))", false),
+            ("(any(test, feature = \"foo\"))", true),
+            ("(any())", false),
+            ("(all())", true),
+        ] {
+            assert_eq!(
+                enabled(predicate, &set).unwrap(),
+                *expected,
+                "{}",
+                predicate
+            );
+        }
+
+        for predicate in &[
+            "(feature = 1)",
+            "(not(test, debug_assertions))",
+            "(either(test))",
+            "(all(test, \"foo\"))",
+            "",
+        ] {
+            assert!(enabled(predicate, &set).is_err(), "{}", predicate);
+        }
+    }
+
+    #[test]
+    fn implies() {
+        for (premises, conclusions, expected) in &[
+            ("", "", true),
+            ("", "#[cfg(feature = \"a\")]", false),
+            ("#[cfg(feature = \"a\")]", "", true),
+            ("#[cfg(feature = \"a\")]", "#[cfg(feature = \"a\")]", true),
+            ("#[cfg(feature = \"a\")]", "#[cfg(feature = \"b\")]", false),
+            (
+                "#[cfg(all(feature = \"a\", feature = \"b\"))]",
+                "#[cfg(feature = \"b\")]",
+                true,
+            ),
+            (
+                "#[cfg(feature = \"a\")] #[cfg(feature = \"b\")]",
+                "#[cfg(all(feature = \"b\", feature = \"a\"))]",
+                true,
+            ),
+            (
+                "#[cfg(feature = \"a\")]",
+                "#[cfg(any(feature = \"a\", feature = \"b\"))]",
+                true,
+            ),
+            (
+                "#[cfg(any(feature = \"a\", feature = \"b\"))]",
+                "#[cfg(feature = \"a\")]",
+                false,
+            ),
+            ("#[cfg(not(test))]", "#[cfg(not(test))]", true),
+        ] {
+            assert_eq!(
+                super::implies(&cfgs(premises), &cfgs(conclusions)).unwrap(),
+                *expected,
+                "{} => {}",
+                premises,
+                conclusions
+            );
+        }
+    }
+}
@@ -1,19 +1,19 @@
 use std::collections::HashSet;
 
-use syn::parse;
+use syn::{parse, spanned::Spanned, Ident};
 
-use crate::ast::App;
+use crate::{
+    ast::{App, FieldPath, LocalResource, SharedResource, TaskLocal, TaskRef},
+    cfg, device, duration, suggest, Map, Settings,
+};
 
-pub fn app(app: &App) -> parse::Result<()> {
+pub fn app(app: &App, settings: &Settings) -> parse::Result<()> {
     // Check that all referenced resources have been declared
     // Check that resources are NOT `Exclusive`-ly shared
     let mut owners = HashSet::new();
     for (_, name, access) in app.shared_resource_accesses() {
         if app.shared_resources.get(name).is_none() {
-            return Err(parse::Error::new(
-                name.span(),
-                "this shared resource has NOT been declared",
-            ));
+            return Err(undeclared_shared(name, &app.shared_resources));
         }
 
         if access.is_exclusive() {
@@ -21,12 +21,41 @@ pub fn app(app: &App) -> parse::Result<()> {
         }
     }
 
+    for (_, path, _) in app.shared_field_accesses() {
+        if app.shared_resources.get(&path.resource).is_none() {
+            return Err(undeclared_shared(&path.resource, &app.shared_resources));
+        }
+    }
+
     for name in app.local_resource_accesses() {
         if app.local_resources.get(name).is_none() {
-            return Err(parse::Error::new(
-                name.span(),
-                "this local resource has NOT been declared",
-            ));
+            return Err(undeclared_local(name, &app.local_resources));
+        }
+    }
+
+    // Check that every task is disabled whenever the resources it accesses are. `init` and `idle`
+    // can't be disabled, so their accesses to disabled resources are disabled along with them
+    for context in app.contexts() {
+        if let TaskRef::Init(_) | TaskRef::Idle(_) = context {
+            continue;
+        }
+
+        let shared = context
+            .shared_resources()
+            .map(|(name, _)| name)
+            .chain(context.shared_fields().map(|(path, _)| &path.resource));
+        for name in shared {
+            if !cfg::implies(context.cfgs(), &app.shared_resources[name].cfgs)? {
+                return Err(disabled_resource(name, "shared"));
+            }
+        }
+
+        for (name, task_local) in context.local_resources() {
+            if let TaskLocal::External = task_local {
+                if !cfg::implies(context.cfgs(), &app.local_resources[name].cfgs)? {
+                    return Err(disabled_resource(name, "local"));
+                }
+            }
         }
     }
 
@@ -43,13 +72,221 @@ pub fn app(app: &App) -> parse::Result<()> {
         .collect::<HashSet<_>>();
     for (_, name, access) in app.shared_resource_accesses() {
         if access.is_shared() && exclusive_accesses.contains(name) {
+            return Err(mixed_accesses(name));
+        }
+    }
+
+    // Same for field paths
+    let exclusive_field_accesses = app
+        .shared_field_accesses()
+        .filter(|(_, _, access)| access.is_exclusive())
+        .map(|(_, path, _)| path)
+        .collect::<HashSet<_>>();
+    for (_, path, access) in app.shared_field_accesses() {
+        if access.is_shared() && exclusive_field_accesses.contains(path) {
+            return Err(mixed_accesses(&path.resource));
+        }
+    }
+
+    // Check that a resource and its fields, or a field and its own fields, aren't both accessed
+    // if any of the accesses is exclusive; their locks would be unrelated
+    let accesses = app
+        .shared_resource_accesses()
+        .map(|(_, name, access)| (FieldPath::resource(name.clone()), access))
+        .chain(
+            app.shared_field_accesses()
+                .map(|(_, path, access)| (path.clone(), access)),
+        )
+        .collect::<Vec<_>>();
+    for (i, (path, access)) in accesses.iter().enumerate() {
+        let overlapping = accesses[..i].iter().find(|(other, other_access)| {
+            other != path
+                && other.overlaps(path)
+                && (access.is_exclusive() || other_access.is_exclusive())
+        });
+
+        if let Some((other, _)) = overlapping {
             return Err(parse::Error::new(
-                name.span(),
-                "this implementation doesn't support shared (`&-`) - exclusive (`&mut-`) locks; use `x` instead of `&x`",
+                path.resource.span(),
+                format!(
+                    "`{}` overlaps with `{}`; a resource and its fields can't be accessed separately if either access is exclusive",
+                    path, other
+                ),
             ));
         }
     }
 
+    // Check that dispatchers are vectors of the target device
+    for name in app.args.extern_interrupts.keys() {
+        device::check(name, settings)?;
+    }
+
+    // Check that tasks bound to exceptions that can't be masked don't access shared resources
+    for task in app.hardware_tasks.values() {
+        let binds = &task.args.binds;
+        let exception = settings.target.exception(&binds.to_string());
+
+        let shared = task
+            .args
+            .shared_resources
+            .keys()
+            .chain(task.args.shared_fields.keys().map(|path| &path.resource))
+            .next();
+        if let (Some(exception), Some(name)) = (exception, shared) {
+            if !exception.is_configurable() {
+                return Err(parse::Error::new(
+                    name.span(),
+                    format!(
+                        "tasks bound to `{}` can't access shared resources; this exception can't be masked",
+                        exception.name
+                    ),
+                ));
+            }
+        }
+    }
+
+    // Check that at most one monotonic is the default one
+    if let Some(monotonic) = app
+        .monotonics
+        .values()
+        .filter(|monotonic| monotonic.args.default)
+        .nth(1)
+    {
+        return Err(parse::Error::new(
+            monotonic.ident.span(),
+            "only one monotonic can be marked `default = true`",
+        ));
+    }
+
+    // Check that software tasks are scheduled on monotonics that have been declared
+    for task in app.software_tasks.values() {
+        if let Some(name) = &task.args.monotonic {
+            if !app.monotonics.contains_key(name) {
+                return Err(parse::Error::new(
+                    name.span(),
+                    suggest::did_you_mean(
+                        "this monotonic has NOT been declared",
+                        &name.to_string(),
+                        app.monotonics.keys().map(|k| k.to_string()),
+                    ),
+                ));
+            }
+        }
+    }
+
+    // Check that tasks only use channels that have been declared and that are enabled whenever the
+    // tasks are
+    for context in app.contexts() {
+        for name in context.send_channels().chain(context.recv_channels()) {
+            let channel = app.message_channels.get(name).ok_or_else(|| {
+                parse::Error::new(
+                    name.span(),
+                    suggest::did_you_mean(
+                        "this channel has NOT been declared",
+                        &name.to_string(),
+                        app.message_channels.keys().map(|k| k.to_string()),
+                    ),
+                )
+            })?;
+
+            if !cfg::implies(context.cfgs(), &channel.cfgs)? {
+                return Err(parse::Error::new(
+                    name.span(),
+                    "this channel may be disabled while the task is enabled; the `#[cfg]` attributes of the task must imply those of the channel",
+                ));
+            }
+        }
+    }
+
+    // Check that contexts only declare spawning software tasks that exist whenever they do
+    for context in app.contexts() {
+        for name in context.spawned_tasks() {
+            let task = app.software_tasks.get(name).ok_or_else(|| {
+                parse::Error::new(
+                    name.span(),
+                    suggest::did_you_mean(
+                        "this software task has NOT been declared",
+                        &name.to_string(),
+                        app.software_tasks.keys().map(|k| k.to_string()),
+                    ),
+                )
+            })?;
+
+            if !cfg::implies(context.cfgs(), &task.cfgs)? {
+                return Err(parse::Error::new(
+                    name.span(),
+                    "this task may be disabled while the spawning context is enabled; the `#[cfg]` attributes of the spawning context must imply those of the task",
+                ));
+            }
+        }
+    }
+
+    // Check that priority 0 tasks get to run: they are run by `idle` whenever it yields
+    if let Some((name, _)) = app
+        .software_tasks
+        .iter()
+        .find(|(_, task)| task.args.priority == 0)
+    {
+        match &app.idle {
+            None => {
+                return Err(parse::Error::new(
+                    name.span(),
+                    "priority 0 tasks run inside `#[idle]` but no `#[idle]` function has been defined",
+                ));
+            }
+
+            Some(idle) if !idle.yields() => {
+                return Err(parse::Error::new(
+                    idle.name.span(),
+                    "this `#[idle]` function never yields so priority 0 tasks can't run; call `yield_now()` from its loop",
+                ));
+            }
+
+            Some(_) => {}
+        }
+    }
+
+    // Check that periodic tasks take no messages and have a monotonic to run on
+    for (name, task) in &app.software_tasks {
+        let period = match &task.args.period {
+            Some(period) => period,
+            None => continue,
+        };
+
+        if let Some(input) = task.inputs.first() {
+            return Err(parse::Error::new(
+                input.pat.span(),
+                "periodic tasks can't take message inputs",
+            ));
+        }
+
+        let monotonic = match &task.args.monotonic {
+            Some(name) => &app.monotonics[name],
+            None => app.default_monotonic().ok_or_else(|| {
+                parse::Error::new(
+                    name.span(),
+                    "periodic tasks need a `monotonic` argument unless a monotonic is marked `default = true`",
+                )
+            })?,
+        };
+
+        // Literal durations must be representable by the monotonic, if its tick rate is known
+        if monotonic.args.tick_hz.is_some() {
+            if duration::literal(period).is_some() && monotonic.duration_to_ticks(period)? == 0 {
+                return Err(parse::Error::new(
+                    period.span(),
+                    "the period must be at least one tick long",
+                ));
+            }
+
+            if let Some(offset) = &task.args.offset {
+                if duration::literal(offset).is_some() {
+                    monotonic.duration_to_ticks(offset)?;
+                }
+            }
+        }
+    }
+
     // check that external interrupts are not used as hardware tasks
     for task in app.hardware_tasks.values() {
         let binds = &task.args.binds;
@@ -62,5 +299,122 @@ pub fn app(app: &App) -> parse::Result<()> {
         }
     }
 
+    // check that external interrupts are not used by monotonics
+    for monotonic in app.monotonics.values() {
+        let binds = &monotonic.args.binds;
+
+        if app.args.extern_interrupts.contains_key(binds) {
+            return Err(parse::Error::new(
+                binds.span(),
+                "dispatcher interrupts can't be used by monotonics",
+            ));
+        }
+    }
+
     Ok(())
 }
+
+// Removes the accesses to resources that have not been declared, reporting each one of them
+pub fn remove_undeclared(app: &mut App) -> Vec<parse::Error> {
+    let App {
+        idle,
+        hardware_tasks,
+        software_tasks,
+        shared_resources,
+        local_resources,
+        ..
+    } = app;
+
+    let mut errors = vec![];
+    let contexts = idle
+        .iter_mut()
+        .map(|idle| {
+            (
+                &mut idle.args.shared_resources,
+                &mut idle.args.shared_fields,
+                &mut idle.args.local_resources,
+            )
+        })
+        .chain(hardware_tasks.values_mut().map(|task| {
+            (
+                &mut task.args.shared_resources,
+                &mut task.args.shared_fields,
+                &mut task.args.local_resources,
+            )
+        }))
+        .chain(software_tasks.values_mut().map(|task| {
+            (
+                &mut task.args.shared_resources,
+                &mut task.args.shared_fields,
+                &mut task.args.local_resources,
+            )
+        }));
+
+    for (shared, fields, local) in contexts {
+        shared.retain(|name, _| {
+            let declared = shared_resources.contains_key(name);
+            if !declared {
+                errors.push(undeclared_shared(name, shared_resources));
+            }
+            declared
+        });
+
+        fields.retain(|path, _| {
+            let declared = shared_resources.contains_key(&path.resource);
+            if !declared {
+                errors.push(undeclared_shared(&path.resource, shared_resources));
+            }
+            declared
+        });
+
+        local.retain(|name, task_local| {
+            let declared =
+                !matches!(task_local, TaskLocal::External) || local_resources.contains_key(name);
+            if !declared {
+                errors.push(undeclared_local(name, local_resources));
+            }
+            declared
+        });
+    }
+
+    errors
+}
+
+fn mixed_accesses(name: &Ident) -> parse::Error {
+    parse::Error::new(
+        name.span(),
+        "this implementation doesn't support shared (`&-`) - exclusive (`&mut-`) locks; use `x` instead of `&x`",
+    )
+}
+
+fn undeclared_shared(name: &Ident, declared: &Map<SharedResource>) -> parse::Error {
+    parse::Error::new(
+        name.span(),
+        suggest::did_you_mean(
+            "this shared resource has NOT been declared",
+            &name.to_string(),
+            declared.keys().map(|k| k.to_string()),
+        ),
+    )
+}
+
+fn undeclared_local(name: &Ident, declared: &Map<LocalResource>) -> parse::Error {
+    parse::Error::new(
+        name.span(),
+        suggest::did_you_mean(
+            "this local resource has NOT been declared",
+            &name.to_string(),
+            declared.keys().map(|k| k.to_string()),
+        ),
+    )
+}
+
+fn disabled_resource(name: &Ident, kind: &str) -> parse::Error {
+    parse::Error::new(
+        name.span(),
+        format!(
+            "this {} resource may be disabled while the task is enabled; the `#[cfg]` attributes of the task must imply those of the resource",
+            kind
+        ),
+    )
+}
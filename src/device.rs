@@ -0,0 +1,138 @@
+//! Interrupt vectors of the target device
+
+use std::fs;
+
+use proc_macro2::Span;
+use syn::{parse, Ident};
+
+use crate::{
+    suggest,
+    target::{self, Target},
+    Settings,
+};
+
+/// Loads the interrupt vectors listed in `settings.svd`, if any, into `settings.device_vectors`
+pub(crate) fn load(settings: &mut Settings) -> parse::Result<()> {
+    if let Some(path) = &settings.svd {
+        let svd = fs::read_to_string(path).map_err(|e| {
+            parse::Error::new(
+                Span::call_site(),
+                format!("couldn't read the SVD file `{}`: {}", path.display(), e),
+            )
+        })?;
+
+        let vectors = settings.device_vectors.get_or_insert_with(Vec::new);
+        for name in svd_interrupts(&svd) {
+            if !vectors.contains(&name) {
+                vectors.push(name);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that `vector` is one of the vectors of the target device, if those are known, and that
+/// it's not reserved by the target architecture
+pub(crate) fn check(vector: &Ident, settings: &Settings) -> parse::Result<()> {
+    if settings.target.profile().is_reserved(&vector.to_string()) {
+        return Err(parse::Error::new(
+            vector.span(),
+            "this vector is reserved by the target architecture",
+        ));
+    }
+
+    let vectors = if let Some(vectors) = &settings.device_vectors {
+        vectors
+    } else {
+        return Ok(());
+    };
+
+    // core exceptions are not part of the device description; `Target::Generic`, the default,
+    // knows no exceptions so it accepts the Cortex-M ones
+    let name = vector.to_string();
+    let generic_exception =
+        settings.target == Target::Generic && target::is_cortex_m_exception(&name);
+    if vectors.contains(&name) || settings.target.exception(&name).is_some() || generic_exception {
+        return Ok(());
+    }
+
+    Err(parse::Error::new(
+        vector.span(),
+        suggest::did_you_mean(
+            "this interrupt is not a vector of the target device",
+            &name,
+            vectors
+                .iter()
+                .map(|v| &**v)
+                .chain(settings.target.exceptions().iter().map(|e| e.name)),
+        ),
+    ))
+}
+
+/// Extracts the names of all the `<interrupt>`s declared in an SVD file
+fn svd_interrupts(svd: &str) -> Vec<String> {
+    let mut names = vec![];
+
+    let mut rest = svd;
+    while let Some(start) = rest.find("<interrupt>") {
+        rest = &rest[start + "<interrupt>".len()..];
+
+        let end = rest.find("</interrupt>").unwrap_or(rest.len());
+        let interrupt = &rest[..end];
+        rest = &rest[end..];
+
+        if let Some(name) = element(interrupt, "name") {
+            if !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
+            }
+        }
+    }
+
+    names
+}
+
+/// The trimmed text of the first `<tag>` element in `xml`
+fn element<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+
+    let start = xml.find(&open)? + open.len();
+    let end = start + xml[start..].find(&close)?;
+
+    Some(xml[start..end].trim())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn svd_interrupts() {
+        let svd = r#"
+            <device>
+              <peripherals>
+                <peripheral>
+                  <name>UART0</name>
+                  <interrupt>
+                    <name>UART0</name>
+                    <value>5</value>
+                  </interrupt>
+                </peripheral>
+                <peripheral derivedFrom="UART0">
+                  <name>UART1</name>
+                  <interrupt>
+                    <name> UART1 </name>
+                    <description>UART1 interrupt</description>
+                    <value>6</value>
+                  </interrupt>
+                  <interrupt>
+                    <name>UART0</name>
+                    <value>5</value>
+                  </interrupt>
+                </peripheral>
+              </peripherals>
+            </device>
+        "#;
+
+        assert_eq!(super::svd_interrupts(svd), ["UART0", "UART1"]);
+    }
+}
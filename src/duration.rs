@@ -0,0 +1,93 @@
+//! Conversion of literal durations into monotonic ticks
+
+use syn::{parse, spanned::Spanned, Expr, ExprLit, Lit};
+
+use crate::ast::Monotonic;
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+// Supported units and their length in nanoseconds
+const UNITS: &[(&str, u128)] = &[
+    ("nanos", 1),
+    ("micros", 1_000),
+    ("millis", 1_000_000),
+    ("secs", NANOS_PER_SEC),
+    ("minutes", 60 * NANOS_PER_SEC),
+    ("hours", 3_600 * NANOS_PER_SEC),
+];
+
+// The value and the unit, in nanoseconds, of a duration literal like `10.millis()`
+pub(crate) fn literal(expr: &Expr) -> Option<(u128, u128)> {
+    match expr {
+        Expr::Group(group) => literal(&group.expr),
+        Expr::Paren(paren) => literal(&paren.expr),
+        Expr::MethodCall(call) if call.args.is_empty() && call.turbofish.is_none() => {
+            let (_, nanos) = UNITS.iter().find(|(unit, _)| call.method == unit)?;
+
+            match &*call.receiver {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Int(value),
+                    ..
+                }) if value.suffix().is_empty() => Some((value.base10_parse().ok()?, *nanos)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+impl Monotonic {
+    /// Converts `duration`, a literal like `10.millis()` or `500.micros()`, into ticks of this
+    /// monotonic
+    ///
+    /// The supported units are `nanos`, `micros`, `millis`, `secs`, `minutes` and `hours`. Errors
+    /// if the tick rate of the monotonic (`tick_hz`) is unknown, if `duration` is not a whole
+    /// number of ticks or if it doesn't fit in the timer (`bits`, 64 if unknown).
+    pub fn duration_to_ticks(&self, duration: &Expr) -> parse::Result<u64> {
+        let span = duration.span();
+        let tick_hz = self.args.tick_hz.ok_or_else(|| {
+            parse::Error::new(
+                span,
+                format!(
+                    "the tick rate of `{}` is unknown; add `tick_hz = ..` to its `#[monotonic]` attribute",
+                    self.ident
+                ),
+            )
+        })?;
+        let (value, nanos) = literal(duration).ok_or_else(|| {
+            parse::Error::new(span, "expected a duration literal like `10.millis()`")
+        })?;
+
+        let bits = self.args.bits.unwrap_or(64);
+        let overflow = || {
+            parse::Error::new(
+                span,
+                format!(
+                    "this duration overflows the {}-bit timer of `{}`",
+                    bits, self.ident
+                ),
+            )
+        };
+
+        let scaled = value
+            .checked_mul(nanos)
+            .and_then(|nanos| nanos.checked_mul(u128::from(tick_hz)))
+            .ok_or_else(overflow)?;
+        if scaled % NANOS_PER_SEC != 0 {
+            return Err(parse::Error::new(
+                span,
+                format!(
+                    "this duration is not a whole number of ticks of `{}` ({} Hz)",
+                    self.ident, tick_hz
+                ),
+            ));
+        }
+
+        let ticks = scaled / NANOS_PER_SEC;
+        if ticks > u128::from(u64::MAX >> (64 - u32::from(bits))) {
+            return Err(overflow());
+        }
+
+        Ok(ticks as u64)
+    }
+}
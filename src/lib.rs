@@ -5,24 +5,35 @@
 #![deny(rust_2018_idioms)]
 #![deny(warnings)]
 
+#[cfg(feature = "proc-macro")]
 #[allow(unused_extern_crates)]
 extern crate proc_macro;
 
 use core::ops;
+#[cfg(feature = "proc-macro")]
 use proc_macro::TokenStream;
+use std::path::PathBuf;
 
 use indexmap::{IndexMap, IndexSet};
 use proc_macro2::TokenStream as TokenStream2;
-use syn::Ident;
+use syn::{Attribute, Ident};
 
-use crate::ast::App;
+use crate::ast::{Access, App, FieldPath, TaskLocal, TaskRef};
 
 mod accessors;
 pub mod analyze;
 pub mod ast;
+pub mod cfg;
 mod check;
+mod device;
+mod duration;
+mod normalize;
 mod optimize;
 mod parse;
+mod suggest;
+pub mod target;
+#[cfg(feature = "testing")]
+pub mod testing;
 #[cfg(test)]
 mod tests;
 
@@ -66,9 +77,25 @@ pub enum Context<'a> {
 
     /// A hardware task: `#[exception]` or `#[interrupt]`
     HardwareTask(&'a Ident),
+
+    /// The interrupt handler of a monotonic: `#[monotonic]`
+    Monotonic(&'a Ident),
 }
 
 impl<'a> Context<'a> {
+    /// All the contexts of `app`: `init`, `idle`, the tasks and the monotonic handlers
+    pub fn all(app: &'a App) -> Vec<Context<'a>> {
+        app.contexts()
+            .map(|context| match context {
+                TaskRef::Init(_) => Context::Init,
+                TaskRef::Idle(_) => Context::Idle,
+                TaskRef::HardwareTask(name, _) => Context::HardwareTask(name),
+                TaskRef::SoftwareTask(name, _) => Context::SoftwareTask(name),
+            })
+            .chain(app.monotonics.keys().map(Context::Monotonic))
+            .collect()
+    }
+
     /// The identifier of this context
     pub fn ident(&self, app: &'a App) -> &'a Ident {
         match self {
@@ -76,6 +103,72 @@ impl<'a> Context<'a> {
             Context::Idle => &app.idle.as_ref().unwrap().name,
             Context::Init => &app.init.name,
             Context::SoftwareTask(ident) => ident,
+            Context::Monotonic(ident) => ident,
+        }
+    }
+
+    // The `init`, `idle` or task this context refers to; `None` for monotonic handlers
+    fn task(&self, app: &'a App) -> Option<TaskRef<'a>> {
+        match *self {
+            Context::HardwareTask(name) => {
+                Some(TaskRef::HardwareTask(name, &app.hardware_tasks[name]))
+            }
+            Context::Idle => Some(TaskRef::Idle(app.idle.as_ref().unwrap())),
+            Context::Init => Some(TaskRef::Init(&app.init)),
+            Context::SoftwareTask(name) => {
+                Some(TaskRef::SoftwareTask(name, &app.software_tasks[name]))
+            }
+            Context::Monotonic(_) => None,
+        }
+    }
+
+    /// The priority this context runs at
+    ///
+    /// A monotonic handler without an explicit priority runs at the highest priority of the
+    /// software tasks that can be scheduled on it, whether they're enabled or not; see
+    /// [`TimerQueue::priority`](analyze::TimerQueue::priority) for the enabled tasks only
+    pub fn priority(&self, app: &App) -> u8 {
+        match *self {
+            Context::Monotonic(name) => app.monotonic_priority(name, app.software_tasks.values()),
+            _ => self.task(app).unwrap().priority(),
+        }
+    }
+
+    /// The shared resources this context can access
+    pub fn shared_resources(
+        &self,
+        app: &'a App,
+    ) -> impl Iterator<Item = (&'a Ident, &'a Access)> + 'a {
+        self.task(app)
+            .into_iter()
+            .flat_map(|task| task.shared_resources())
+    }
+
+    /// The fields of shared resources this context locks separately, e.g. `shared = [state.counter]`
+    pub fn shared_fields(
+        &self,
+        app: &'a App,
+    ) -> impl Iterator<Item = (&'a FieldPath, &'a Access)> + 'a {
+        self.task(app)
+            .into_iter()
+            .flat_map(|task| task.shared_fields())
+    }
+
+    /// The local resources this context can access or declares
+    pub fn local_resources(
+        &self,
+        app: &'a App,
+    ) -> impl Iterator<Item = (&'a Ident, &'a TaskLocal)> + 'a {
+        self.task(app)
+            .into_iter()
+            .flat_map(|task| task.local_resources().iter())
+    }
+
+    /// `#[cfg]` attributes of this context
+    pub fn cfgs(&self, app: &'a App) -> &'a [Attribute] {
+        match *self {
+            Context::Monotonic(name) => &app.monotonics[name].cfgs,
+            _ => self.task(app).unwrap().cfgs(),
         }
     }
 
@@ -94,17 +187,23 @@ impl<'a> Context<'a> {
         self.is_init() || self.is_idle()
     }
 
-    /// Whether this context has shared resources
+    /// Whether this context has shared resources, as a whole or through some of their fields
     pub fn has_shared_resources(&self, app: &App) -> bool {
         match *self {
             Context::HardwareTask(name) => {
-                !app.hardware_tasks[name].args.shared_resources.is_empty()
+                let args = &app.hardware_tasks[name].args;
+                !args.shared_resources.is_empty() || !args.shared_fields.is_empty()
+            }
+            Context::Idle => {
+                let args = &app.idle.as_ref().unwrap().args;
+                !args.shared_resources.is_empty() || !args.shared_fields.is_empty()
             }
-            Context::Idle => !app.idle.as_ref().unwrap().args.shared_resources.is_empty(),
             Context::Init => false,
             Context::SoftwareTask(name) => {
-                !app.software_tasks[name].args.shared_resources.is_empty()
+                let args = &app.software_tasks[name].args;
+                !args.shared_resources.is_empty() || !args.shared_fields.is_empty()
             }
+            Context::Monotonic(_) => false,
         }
     }
 
@@ -119,6 +218,7 @@ impl<'a> Context<'a> {
             Context::SoftwareTask(name) => {
                 !app.software_tasks[name].args.local_resources.is_empty()
             }
+            Context::Monotonic(_) => false,
         }
     }
 }
@@ -133,9 +233,30 @@ pub struct Settings {
     pub parse_extern_interrupt: bool,
     /// Whether to "compress" priorities or not
     pub optimize_priorities: bool,
+    /// Names of the interrupt vectors of the target device
+    ///
+    /// When known, every `binds` and `dispatchers` entry must be one of these
+    pub device_vectors: Option<Vec<String>>,
+    /// Path to an SVD file describing the target device
+    ///
+    /// The interrupts it declares are added to `device_vectors`
+    pub svd: Option<PathBuf>,
+    /// The architecture the application is compiled for
+    pub target: target::Target,
+    /// Path of the attribute that marks the application module, e.g. `rtic::app`
+    ///
+    /// Only used by `find_app`, `parse_file` and `parse_str`. When `None`, any attribute whose
+    /// last path segment is `app` matches
+    pub app_attribute: Option<syn::Path>,
+    /// The active `cfg` options
+    ///
+    /// When set, the analysis only considers the tasks, resources and monotonics whose `#[cfg]`
+    /// attributes hold. When `None`, every `#[cfg]` is assumed to hold
+    pub cfgs: Option<cfg::CfgSet>,
 }
 
 /// Parses the input of the `#[app]` attribute
+#[cfg(feature = "proc-macro")]
 pub fn parse(
     args: TokenStream,
     input: TokenStream,
@@ -148,19 +269,100 @@ pub fn parse(
 pub fn parse2(
     args: TokenStream2,
     input: TokenStream2,
-    settings: Settings,
+    mut settings: Settings,
 ) -> Result<(P<ast::App>, P<analyze::Analysis>), syn::parse::Error> {
+    device::load(&mut settings)?;
+
     let mut app = parse::app(args, input, &settings)?;
-    check::app(&app)?;
-    optimize::app(&mut app, &settings);
+    check::app(&app, &settings)?;
+    optimize::app(&mut app, &settings)?;
 
-    match analyze::app(&app) {
+    match analyze::app(&app, &settings) {
         Err(e) => Err(e),
         // If no errors, return the app and analysis results
         Ok(analysis) => Ok((P::new(app), P::new(analysis))),
     }
 }
 
+/// Locates the `#[app]` module among the items of `file`
+///
+/// Returns the arguments of the attribute and the module without the attribute, ready to be passed
+/// to `parse2`. It's an error if `file` contains no or several `#[app]` modules.
+pub fn find_app(
+    file: &syn::File,
+    settings: &Settings,
+) -> Result<(TokenStream2, TokenStream2), syn::parse::Error> {
+    parse::file(file, settings)
+}
+
+/// Parses the `#[app]` module of `file`
+pub fn parse_file(
+    file: &syn::File,
+    settings: Settings,
+) -> Result<(P<ast::App>, P<analyze::Analysis>), syn::parse::Error> {
+    let (args, input) = find_app(file, &settings)?;
+    parse2(args, input, settings)
+}
+
+/// Parses the `#[app]` module of the Rust source code `source`
+pub fn parse_str(
+    source: &str,
+    settings: Settings,
+) -> Result<(P<ast::App>, P<analyze::Analysis>), syn::parse::Error> {
+    parse_file(&syn::parse_file(source)?, settings)
+}
+
+/// Error-recovering version of `parse2`, meant for tools like IDEs
+///
+/// Items that fail to parse are skipped; whatever survives is still checked and analyzed. A missing
+/// or broken `#[init]` function is replaced by an empty one named `init`. Returns the partial
+/// application, its analysis (if it could be computed) and every error found.
+pub fn parse_recovering(
+    args: TokenStream2,
+    input: TokenStream2,
+    mut settings: Settings,
+) -> (
+    Option<P<ast::App>>,
+    Option<P<analyze::Analysis>>,
+    Vec<syn::parse::Error>,
+) {
+    let mut errors = vec![];
+
+    if let Err(e) = device::load(&mut settings) {
+        errors.push(e);
+    }
+
+    let mut app = match parse::app_recovering(args, input, &settings, &mut errors) {
+        Ok(app) => app,
+        Err(e) => {
+            errors.push(e);
+            return (None, None, errors.into_iter().flatten().collect());
+        }
+    };
+
+    // the analysis assumes that every referenced resource exists
+    errors.extend(check::remove_undeclared(&mut app));
+    if let Err(e) = check::app(&app, &settings) {
+        errors.push(e);
+    }
+    if let Err(e) = optimize::app(&mut app, &settings) {
+        errors.push(e);
+    }
+
+    let analysis = match analyze::app(&app, &settings) {
+        Ok(analysis) => Some(P::new(analysis)),
+        Err(e) => {
+            errors.push(e);
+            None
+        }
+    };
+
+    // one entry per error, instead of combined errors
+    let errors = errors.into_iter().flatten().collect();
+
+    (Some(P::new(app)), analysis, errors)
+}
+
 enum Either<A, B> {
     Left(A),
     Right(B),
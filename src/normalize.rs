@@ -0,0 +1,191 @@
+//! Canonical forms of types, used to compare types that are written differently
+
+use std::collections::HashMap;
+
+use syn::{
+    punctuated::Punctuated, GenericArgument, Ident, ItemUse, Path, PathArguments, PathSegment,
+    ReturnType, Token, Type, TypePath, UseTree,
+};
+
+use crate::ast::NormalizedType;
+
+// Primitive types; `core::primitive::u32` and `std::primitive::u32` are spelled `u32`
+const PRIMITIVES: &[&str] = &[
+    "bool", "char", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "str", "u8", "u16",
+    "u32", "u64", "u128", "usize",
+];
+
+/// The names brought into scope by the `use` items of the `#[app]` module
+pub(crate) struct Imports {
+    // name in scope -> full path
+    names: HashMap<Ident, Vec<Ident>>,
+}
+
+impl Imports {
+    pub(crate) fn new(items: &[ItemUse]) -> Self {
+        let mut names = HashMap::new();
+        for item in items {
+            collect(&item.tree, &mut vec![], &mut names);
+        }
+
+        Imports { names }
+    }
+
+    /// Pairs `ty` with its canonical form
+    pub(crate) fn normalize(&self, ty: &Type) -> NormalizedType {
+        NormalizedType {
+            original: Box::new(ty.clone()),
+            normalized: Box::new(self.ty(ty)),
+        }
+    }
+
+    fn ty(&self, ty: &Type) -> Type {
+        let mut ty = ty.clone();
+        self.visit(&mut ty);
+        ty
+    }
+
+    fn visit(&self, ty: &mut Type) {
+        match ty {
+            // `(T)` is `T`
+            Type::Group(group) => {
+                let elem = self.ty(&group.elem);
+                *ty = elem;
+            }
+            Type::Paren(paren) => {
+                let elem = self.ty(&paren.elem);
+                *ty = elem;
+            }
+
+            Type::Array(array) => self.visit(&mut array.elem),
+            Type::Ptr(ptr) => self.visit(&mut ptr.elem),
+            Type::Reference(reference) => self.visit(&mut reference.elem),
+            Type::Slice(slice) => self.visit(&mut slice.elem),
+            Type::Tuple(tuple) => tuple.elems.iter_mut().for_each(|elem| self.visit(elem)),
+
+            Type::Path(TypePath { qself, path }) => {
+                if let Some(qself) = qself {
+                    self.visit(&mut qself.ty);
+                } else {
+                    self.resolve(path);
+                }
+
+                for segment in &mut path.segments {
+                    self.visit_arguments(&mut segment.arguments);
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    fn visit_arguments(&self, arguments: &mut PathArguments) {
+        match arguments {
+            PathArguments::None => {}
+
+            PathArguments::AngleBracketed(args) => {
+                for arg in &mut args.args {
+                    match arg {
+                        GenericArgument::Type(ty) => self.visit(ty),
+                        GenericArgument::Binding(binding) => self.visit(&mut binding.ty),
+                        _ => {}
+                    }
+                }
+            }
+
+            PathArguments::Parenthesized(args) => {
+                args.inputs.iter_mut().for_each(|input| self.visit(input));
+                if let ReturnType::Type(_, output) = &mut args.output {
+                    self.visit(output);
+                }
+            }
+        }
+    }
+
+    // Expands the first segment of `path` if it was imported, and drops the prefix of primitives
+    fn resolve(&self, path: &mut Path) {
+        // `use` items may refer to each other; bound the expansions in case they form a cycle
+        for _ in 0..=self.names.len() {
+            if path.leading_colon.is_some() {
+                break;
+            }
+
+            let full = match self.names.get(&path.segments[0].ident) {
+                // `use foo::foo` refers to the crate `foo` in its own path
+                Some(full) if full[0] != path.segments[0].ident => full,
+                _ => break,
+            };
+
+            let mut segments = path.segments.clone().into_iter();
+            let first = segments.next().unwrap();
+            let mut expanded = full
+                .iter()
+                .map(|ident| PathSegment::from(ident.clone()))
+                .collect::<Punctuated<_, Token![::]>>();
+            expanded.last_mut().unwrap().arguments = first.arguments;
+            expanded.extend(segments);
+
+            path.segments = expanded;
+        }
+
+        path.leading_colon = None;
+
+        let segments = &path.segments;
+        if segments.len() == 3
+            && (segments[0].ident == "core" || segments[0].ident == "std")
+            && segments[1].ident == "primitive"
+            && PRIMITIVES.iter().any(|name| segments[2].ident == *name)
+        {
+            let primitive = segments[2].clone();
+            path.segments = Some(primitive).into_iter().collect();
+        }
+    }
+}
+
+fn collect(tree: &UseTree, prefix: &mut Vec<Ident>, names: &mut HashMap<Ident, Vec<Ident>>) {
+    match tree {
+        UseTree::Path(path) => {
+            prefix.push(path.ident.clone());
+            collect(&path.tree, prefix, names);
+            prefix.pop();
+        }
+
+        UseTree::Name(name) => {
+            if let Some(full) = full_path(prefix, &name.ident) {
+                names.insert(full.last().unwrap().clone(), full);
+            }
+        }
+
+        // `use Trait as _` brings no name into scope
+        UseTree::Rename(rename) if rename.rename == "_" => {}
+        UseTree::Rename(rename) => {
+            if let Some(full) = full_path(prefix, &rename.ident) {
+                names.insert(rename.rename.clone(), full);
+            }
+        }
+
+        UseTree::Group(group) => {
+            for tree in &group.items {
+                collect(tree, prefix, names);
+            }
+        }
+
+        // the names a glob brings into scope are not known
+        UseTree::Glob(_) => {}
+    }
+}
+
+// The path `prefix::ident` names; `a::b::{self}` names `a::b`
+fn full_path(prefix: &[Ident], ident: &Ident) -> Option<Vec<Ident>> {
+    if ident == "self" {
+        if prefix.is_empty() {
+            None
+        } else {
+            Some(prefix.to_vec())
+        }
+    } else {
+        let mut full = prefix.to_vec();
+        full.push(ident.clone());
+        Some(full)
+    }
+}
@@ -1,11 +1,29 @@
-use std::collections::{BTreeSet, HashMap};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    convert::TryFrom,
+};
 
-use crate::{ast::App, Settings};
+use syn::{parse, spanned::Spanned, Ident};
+
+use crate::{
+    ast::{App, PriorityArg, PriorityAssignment, TaskRef},
+    duration, Settings,
+};
+
+pub fn app(app: &mut App, settings: &Settings) -> parse::Result<()> {
+    // Assign priorities from deadlines: the shorter the deadline, the higher the priority
+    for (name, priority) in deadline_monotonic(app, settings)? {
+        if let Some(task) = app.hardware_tasks.get_mut(&name) {
+            task.args.priority = priority;
+        } else if let Some(task) = app.software_tasks.get_mut(&name) {
+            task.args.priority = priority;
+        }
+    }
 
-pub fn app(app: &mut App, settings: &Settings) {
     // "compress" priorities
     // If the user specified, for example, task priorities of "1, 3, 6",
     // compress them into "1, 2, 3" as to leave no gaps
+    // Priority 0 tasks run inside `idle` and keep their priority
     if settings.optimize_priorities {
         // all task priorities ordered in ascending order
         let priorities = app
@@ -22,7 +40,9 @@ pub fn app(app: &mut App, settings: &Settings) {
         let map = priorities
             .iter()
             .cloned()
+            .filter(|priority| *priority != Some(0))
             .zip(1..)
+            .chain(Some((Some(0), 0)))
             .collect::<HashMap<_, _>>();
 
         for task in app.hardware_tasks.values_mut() {
@@ -33,4 +53,132 @@ pub fn app(app: &mut App, settings: &Settings) {
             task.args.priority = map[&Some(task.args.priority)];
         }
     }
+
+    Ok(())
+}
+
+// The tasks whose priority is assigned from their deadline: those with `priority = auto` and, with
+// `#[app(priorities = "deadline-monotonic")]`, those without a `priority` argument; tasks bound to
+// exceptions whose priority is fixed are left out
+pub(crate) fn auto_priority_tasks<'a>(
+    app: &'a App,
+    settings: &'a Settings,
+) -> impl Iterator<Item = TaskRef<'a>> + 'a {
+    let mode = app.args.priorities;
+
+    app.contexts().filter(move |context| {
+        if let TaskRef::HardwareTask(_, task) = context {
+            let exception = settings.target.exception(&task.args.binds.to_string());
+            if matches!(exception, Some(exception) if !exception.is_configurable()) {
+                return false;
+            }
+        }
+
+        match context.priority_arg() {
+            Some(PriorityArg::Auto) => true,
+            Some(PriorityArg::Omitted) => mode == PriorityAssignment::DeadlineMonotonic,
+            _ => false,
+        }
+    })
+}
+
+// Deadline-monotonic priorities for the `auto_priority_tasks`
+//
+// Tasks are visited from the longest deadline to the shortest (tasks without one come first) and
+// each group of automatic tasks with the same deadline gets the lowest priority that is higher
+// than those of the tasks with longer deadlines. Tasks with an explicit priority and a deadline
+// take part as constraints: their priority must be higher than those assigned to tasks with longer
+// deadlines.
+fn deadline_monotonic(app: &App, settings: &Settings) -> parse::Result<Vec<(Ident, u8)>> {
+    let auto = auto_priority_tasks(app, settings).collect::<Vec<_>>();
+    if auto.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let constraints = app.contexts().filter(|context| {
+        context.priority_arg() == Some(PriorityArg::Explicit)
+            && context.priority() != 0
+            && context.deadline().is_some()
+    });
+
+    // (automatic tasks, constraints), keyed by deadline in nanoseconds; `u128::MAX` if there's none
+    let mut groups = BTreeMap::<u128, (Vec<TaskRef<'_>>, Vec<TaskRef<'_>>)>::new();
+    for context in auto.iter().copied() {
+        groups
+            .entry(deadline(context)?)
+            .or_default()
+            .0
+            .push(context);
+    }
+    for context in constraints {
+        groups
+            .entry(deadline(context)?)
+            .or_default()
+            .1
+            .push(context);
+    }
+
+    let priorities = settings.target.profile().priorities;
+    let mut next = u16::from(*priorities.start());
+    let mut last_assigned = None;
+    let mut assigned = vec![];
+    for (auto, constraints) in groups.values().rev() {
+        for context in constraints {
+            if let Some(priority) = last_assigned {
+                if context.priority() <= priority {
+                    return Err(parse::Error::new(
+                        context.span(),
+                        format!(
+                            "this task has a shorter deadline than tasks that were assigned priority {}; its priority must be higher",
+                            priority
+                        ),
+                    ));
+                }
+            }
+
+            next = next.max(u16::from(context.priority()) + 1);
+        }
+
+        if let Some(context) = auto.first() {
+            let priority = match u8::try_from(next) {
+                Ok(priority) if priorities.contains(&priority) => priority,
+                _ => {
+                    return Err(parse::Error::new(
+                        context.span(),
+                        format!(
+                            "not enough priority levels to assign deadline-monotonic priorities; this task would need priority {} but the range is {}...{}",
+                            next,
+                            priorities.start(),
+                            priorities.end()
+                        ),
+                    ));
+                }
+            };
+
+            assigned.extend(
+                auto.iter()
+                    .map(|context| (context.name().clone(), priority)),
+            );
+            last_assigned = Some(priority);
+            next += 1;
+        }
+    }
+
+    Ok(assigned)
+}
+
+// The deadline of `context` in nanoseconds; `u128::MAX` if it has none
+fn deadline(context: TaskRef<'_>) -> parse::Result<u128> {
+    let deadline = match context.deadline() {
+        Some(deadline) => deadline,
+        None => return Ok(u128::MAX),
+    };
+
+    match duration::literal(deadline) {
+        Some((value, nanos)) => Ok(value.saturating_mul(nanos)),
+        None => Err(parse::Error::new(
+            deadline.span(),
+            "expected a duration literal like `5.millis()` to assign priorities from",
+        )),
+    }
 }
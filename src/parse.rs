@@ -1,4 +1,5 @@
 mod app;
+mod channel;
 mod hardware_task;
 mod idle;
 mod init;
@@ -7,18 +8,20 @@ mod resource;
 mod software_task;
 mod util;
 
-use proc_macro2::TokenStream as TokenStream2;
+use proc_macro2::{Delimiter, Span, TokenStream as TokenStream2, TokenTree};
+use quote::ToTokens;
 use syn::{
     braced, parenthesized,
     parse::{self, Parse, ParseStream, Parser},
+    spanned::Spanned,
     token::Brace,
-    Ident, Item, LitBool, LitInt, Token,
+    Attribute, Expr, Ident, Item, LitBool, LitInt, Token,
 };
 
 use crate::{
     ast::{
-        App, AppArgs, HardwareTaskArgs, IdleArgs, InitArgs, MonotonicArgs, SoftwareTaskArgs,
-        TaskLocal,
+        App, AppArgs, HardwareTaskArgs, IdleArgs, InitArgs, MessageChannelArgs, MonotonicArgs,
+        PriorityArg, SoftwareTaskArgs, TaskLocal,
     },
     Either, Settings,
 };
@@ -31,6 +34,78 @@ pub fn app(args: TokenStream2, input: TokenStream2, settings: &Settings) -> pars
     App::parse(args, input, settings)
 }
 
+// Parse the app, reporting and skipping the items that fail to parse
+pub fn app_recovering(
+    args: TokenStream2,
+    input: TokenStream2,
+    settings: &Settings,
+    errors: &mut Vec<parse::Error>,
+) -> parse::Result<App> {
+    let args = AppArgs::parse(args).unwrap_or_else(|e| {
+        errors.push(e);
+        AppArgs::default()
+    });
+    let input: Input = syn::parse2(input)?;
+
+    App::parse_recovering(args, input, settings, Some(errors))
+}
+
+// Find the app module among the items of `file`, returning the arguments of its attribute and the
+// module without that attribute
+pub fn file(file: &syn::File, settings: &Settings) -> parse::Result<(TokenStream2, TokenStream2)> {
+    let is_app = |attr: &Attribute| match &settings.app_attribute {
+        Some(path) => attr.path == *path,
+        None => matches!(attr.path.segments.last(), Some(segment) if segment.ident == "app"),
+    };
+
+    let mut found = None;
+    for item in &file.items {
+        let module = match item {
+            Item::Mod(module) => module,
+            _ => continue,
+        };
+
+        if let Some(pos) = module.attrs.iter().position(is_app) {
+            if found.is_some() {
+                return Err(parse::Error::new(
+                    module.attrs[pos].path.span(),
+                    "only one `#[app]` module can be defined",
+                ));
+            }
+
+            found = Some((pos, module));
+        }
+    }
+
+    let (pos, module) = found.ok_or_else(|| {
+        parse::Error::new(
+            Span::call_site(),
+            match &settings.app_attribute {
+                Some(path) => {
+                    let path = path
+                        .segments
+                        .iter()
+                        .map(|segment| segment.ident.to_string())
+                        .collect::<Vec<_>>();
+                    format!("no `#[{}]` module found", path.join("::"))
+                }
+                None => "no `#[app]` module found".to_string(),
+            },
+        )
+    })?;
+
+    let mut module = module.clone();
+    let attr = module.attrs.remove(pos);
+    let args = match attr.tokens.into_iter().next() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+            group.stream()
+        }
+        _ => TokenStream2::new(),
+    };
+
+    Ok((args, module.into_token_stream()))
+}
+
 pub(crate) struct Input {
     _mod_token: Token![mod],
     pub ident: Ident,
@@ -73,6 +148,7 @@ fn init_args(tokens: TokenStream2) -> parse::Result<InitArgs> {
         }
 
         let mut local_resources = None;
+        let mut spawn = None;
 
         let content;
         parenthesized!(content in input);
@@ -98,8 +174,19 @@ fn init_args(tokens: TokenStream2) -> parse::Result<InitArgs> {
                     local_resources = Some(util::parse_local_resources(&content)?);
                 }
 
+                "spawn" => {
+                    if spawn.is_some() {
+                        return Err(parse::Error::new(
+                            ident.span(),
+                            "argument appears more than once",
+                        ));
+                    }
+
+                    spawn = Some(util::parse_idents(&content, "task")?);
+                }
+
                 _ => {
-                    return Err(parse::Error::new(ident.span(), "unexpected argument"));
+                    return Err(util::unexpected_argument(&ident, &["local", "spawn"]));
                 }
             }
 
@@ -113,20 +200,18 @@ fn init_args(tokens: TokenStream2) -> parse::Result<InitArgs> {
 
         if let Some(locals) = &local_resources {
             for (ident, task_local) in locals {
-                match task_local {
-                    TaskLocal::External => {
-                        return Err(parse::Error::new(
-                            ident.span(),
-                            "only declared local resources are allowed in init",
-                        ));
-                    }
-                    _ => {}
+                if let TaskLocal::External = task_local {
+                    return Err(parse::Error::new(
+                        ident.span(),
+                        "only declared local resources are allowed in init",
+                    ));
                 }
             }
         }
 
         Ok(InitArgs {
             local_resources: local_resources.unwrap_or_default(),
+            spawn: spawn.unwrap_or_default(),
         })
     })
     .parse2(tokens)
@@ -140,6 +225,7 @@ fn idle_args(tokens: TokenStream2) -> parse::Result<IdleArgs> {
 
         let mut shared_resources = None;
         let mut local_resources = None;
+        let mut spawn = None;
 
         let content;
         parenthesized!(content in input);
@@ -176,8 +262,22 @@ fn idle_args(tokens: TokenStream2) -> parse::Result<IdleArgs> {
                     local_resources = Some(util::parse_local_resources(&content)?);
                 }
 
+                "spawn" => {
+                    if spawn.is_some() {
+                        return Err(parse::Error::new(
+                            ident.span(),
+                            "argument appears more than once",
+                        ));
+                    }
+
+                    spawn = Some(util::parse_idents(&content, "task")?);
+                }
+
                 _ => {
-                    return Err(parse::Error::new(ident.span(), "unexpected argument"));
+                    return Err(util::unexpected_argument(
+                        &ident,
+                        &["shared", "local", "spawn"],
+                    ));
                 }
             }
 
@@ -189,9 +289,13 @@ fn idle_args(tokens: TokenStream2) -> parse::Result<IdleArgs> {
             let _: Token![,] = content.parse()?;
         }
 
+        let (shared_resources, shared_fields) = shared_resources.unwrap_or_default();
+
         Ok(IdleArgs {
-            shared_resources: shared_resources.unwrap_or_default(),
+            shared_resources,
+            shared_fields,
             local_resources: local_resources.unwrap_or_default(),
+            spawn: spawn.unwrap_or_default(),
         })
     })
     .parse2(tokens)
@@ -208,10 +312,18 @@ fn task_args(
 
         let mut binds = None;
         let mut capacity = None;
+        let mut monotonic = None;
+        let mut period = None;
+        let mut offset = None;
         let mut priority = None;
+        let mut priority_span = None;
+        let mut auto = None;
+        let mut deadline = None;
         let mut shared_resources = None;
         let mut local_resources = None;
-
+        let mut send = None;
+        let mut recv = None;
+        let mut spawn = None;
 
         let content;
         parenthesized!(content in input);
@@ -248,6 +360,20 @@ fn task_args(
                         ));
                     }
 
+                    if monotonic.is_some() {
+                        return Err(parse::Error::new(
+                            ident.span(),
+                            "hardware tasks can't use the `monotonic` argument",
+                        ));
+                    }
+
+                    if period.is_some() || offset.is_some() {
+                        return Err(parse::Error::new(
+                            ident.span(),
+                            "hardware tasks can't be periodic",
+                        ));
+                    }
+
                     // #ident
                     let ident = content.parse()?;
 
@@ -290,33 +416,105 @@ fn task_args(
                     capacity = Some(value.unwrap());
                 }
 
-                "priority" => {
-                    if priority.is_some() {
+                "monotonic" => {
+                    if monotonic.is_some() {
                         return Err(parse::Error::new(
                             ident.span(),
                             "argument appears more than once",
                         ));
                     }
 
-                    // #lit
-                    let lit: LitInt = content.parse()?;
+                    if binds.is_some() {
+                        return Err(parse::Error::new(
+                            ident.span(),
+                            "hardware tasks can't use the `monotonic` argument",
+                        ));
+                    }
 
-                    if !lit.suffix().is_empty() {
+                    // #ident
+                    monotonic = Some(content.parse()?);
+                }
+
+                "period" | "offset" => {
+                    let arg = if ident_s == "period" {
+                        &mut period
+                    } else {
+                        &mut offset
+                    };
+
+                    if arg.is_some() {
                         return Err(parse::Error::new(
-                            lit.span(),
-                            "this literal must be unsuffixed",
+                            ident.span(),
+                            "argument appears more than once",
                         ));
                     }
 
-                    let value = lit.base10_parse::<u8>().ok();
-                    if value.is_none() || value == Some(0) {
+                    if binds.is_some() {
                         return Err(parse::Error::new(
-                            lit.span(),
-                            "this literal must be in the range 1...255",
+                            ident.span(),
+                            "hardware tasks can't be periodic",
                         ));
                     }
 
-                    priority = Some(value.unwrap());
+                    // #expr
+                    *arg = Some((ident, Box::new(content.parse::<Expr>()?)));
+                }
+
+                "priority" => {
+                    if priority.is_some() || auto.is_some() {
+                        return Err(parse::Error::new(
+                            ident.span(),
+                            "argument appears more than once",
+                        ));
+                    }
+
+                    // auto
+                    if content.peek(Ident) {
+                        let ident: Ident = content.parse()?;
+                        if ident != "auto" {
+                            return Err(parse::Error::new(
+                                ident.span(),
+                                "expected an integer literal or `auto`",
+                            ));
+                        }
+
+                        auto = Some(ident);
+                    } else {
+                        // #lit
+                        let lit: LitInt = content.parse()?;
+
+                        if !lit.suffix().is_empty() {
+                            return Err(parse::Error::new(
+                                lit.span(),
+                                "this literal must be unsuffixed",
+                            ));
+                        }
+
+                        // Software tasks can also run at priority 0, inside `idle`
+                        let priorities = settings.target.profile().priorities;
+                        let value = lit.base10_parse::<u8>().ok();
+                        if value
+                            .map(|v| v != 0 && !priorities.contains(&v))
+                            .unwrap_or(true)
+                        {
+                            return Err(priority_out_of_range(lit.span(), settings));
+                        }
+
+                        priority = Some(value.unwrap());
+                        priority_span = Some(lit.span());
+                    }
+                }
+
+                "deadline" => {
+                    if deadline.is_some() {
+                        return Err(parse::Error::new(
+                            ident.span(),
+                            "argument appears more than once",
+                        ));
+                    }
+
+                    // #expr
+                    deadline = Some(Box::new(content.parse::<Expr>()?));
                 }
 
                 "shared" => {
@@ -341,8 +539,53 @@ fn task_args(
                     local_resources = Some(util::parse_local_resources(&content)?);
                 }
 
+                "send" | "recv" => {
+                    let arg = if ident_s == "send" {
+                        &mut send
+                    } else {
+                        &mut recv
+                    };
+
+                    if arg.is_some() {
+                        return Err(parse::Error::new(
+                            ident.span(),
+                            "argument appears more than once",
+                        ));
+                    }
+
+                    *arg = Some(util::parse_idents(&content, "channel")?);
+                }
+
+                "spawn" => {
+                    if spawn.is_some() {
+                        return Err(parse::Error::new(
+                            ident.span(),
+                            "argument appears more than once",
+                        ));
+                    }
+
+                    spawn = Some(util::parse_idents(&content, "task")?);
+                }
+
                 _ => {
-                    return Err(parse::Error::new(ident.span(), "unexpected argument"));
+                    let mut expected = vec![
+                        "capacity",
+                        "monotonic",
+                        "period",
+                        "offset",
+                        "priority",
+                        "deadline",
+                        "shared",
+                        "local",
+                        "send",
+                        "recv",
+                        "spawn",
+                    ];
+                    if settings.parse_binds {
+                        expected.push("binds");
+                    }
+
+                    return Err(util::unexpected_argument(&ident, &expected));
                 }
             }
 
@@ -353,34 +596,85 @@ fn task_args(
             // ,
             let _: Token![,] = content.parse()?;
         }
+        if let Some(binds) = &binds {
+            let span = priority_span.or_else(|| auto.as_ref().map(|auto| auto.span()));
+            check_fixed_priority(binds, span, settings)?;
+
+            if let (Some(0), Some(span)) = (priority, priority_span) {
+                return Err(priority_out_of_range(span, settings));
+            }
+        }
+
+        if let (Some(auto), None, None) = (&auto, &deadline, &period) {
+            return Err(parse::Error::new(
+                auto.span(),
+                "`priority = auto` needs a `deadline` or a `period` to assign a priority from",
+            ));
+        }
+
+        if let (Some((ident, _)), None) = (&offset, &period) {
+            return Err(parse::Error::new(
+                ident.span(),
+                "`offset` can only be used together with `period`",
+            ));
+        }
+
+        let priority_arg = if auto.is_some() {
+            PriorityArg::Auto
+        } else if priority.is_some() {
+            PriorityArg::Explicit
+        } else {
+            PriorityArg::Omitted
+        };
         let priority = priority.unwrap_or(1);
-        let shared_resources = shared_resources.unwrap_or_default();
+        let (shared_resources, shared_fields) = shared_resources.unwrap_or_default();
         let local_resources = local_resources.unwrap_or_default();
+        let send = send.unwrap_or_default();
+        let recv = recv.unwrap_or_default();
+        let spawn = spawn.unwrap_or_default();
 
         Ok(if let Some(binds) = binds {
             Either::Left(HardwareTaskArgs {
                 binds,
                 priority,
+                priority_arg,
+                deadline,
                 shared_resources,
+                shared_fields,
                 local_resources,
+                send,
+                recv,
+                spawn,
             })
         } else {
             Either::Right(SoftwareTaskArgs {
                 capacity: capacity.unwrap_or(1),
+                monotonic,
+                period: period.map(|(_, period)| period),
+                offset: offset.map(|(_, offset)| offset),
                 priority,
+                priority_arg,
+                deadline,
                 shared_resources,
+                shared_fields,
                 local_resources,
+                send,
+                recv,
+                spawn,
             })
         })
     })
     .parse2(tokens)
 }
 
-fn monotonic_args(tokens: TokenStream2) -> parse::Result<MonotonicArgs> {
+fn monotonic_args(tokens: TokenStream2, settings: &Settings) -> parse::Result<MonotonicArgs> {
     (|input: ParseStream<'_>| -> parse::Result<MonotonicArgs> {
         let mut binds = None;
         let mut priority = None;
+        let mut priority_span = None;
         let mut default = None;
+        let mut tick_hz = None;
+        let mut bits = None;
 
         let content;
         parenthesized!(content in input);
@@ -427,15 +721,14 @@ fn monotonic_args(tokens: TokenStream2) -> parse::Result<MonotonicArgs> {
                         ));
                     }
 
+                    let priorities = settings.target.profile().priorities;
                     let value = lit.base10_parse::<u8>().ok();
-                    if value.is_none() || value == Some(0) {
-                        return Err(parse::Error::new(
-                            lit.span(),
-                            "this literal must be in the range 1...255",
-                        ));
+                    if value.map(|v| !priorities.contains(&v)).unwrap_or(true) {
+                        return Err(priority_out_of_range(lit.span(), settings));
                     }
 
                     priority = Some(value.unwrap());
+                    priority_span = Some(lit.span());
                 }
 
                 "default" => {
@@ -450,8 +743,69 @@ fn monotonic_args(tokens: TokenStream2) -> parse::Result<MonotonicArgs> {
                     default = Some(lit.value);
                 }
 
+                "tick_hz" => {
+                    if tick_hz.is_some() {
+                        return Err(parse::Error::new(
+                            ident.span(),
+                            "argument appears more than once",
+                        ));
+                    }
+
+                    // #lit
+                    let lit: LitInt = content.parse()?;
+
+                    if !lit.suffix().is_empty() {
+                        return Err(parse::Error::new(
+                            lit.span(),
+                            "this literal must be unsuffixed",
+                        ));
+                    }
+
+                    let value = lit.base10_parse::<u32>().ok();
+                    if value.is_none() || value == Some(0) {
+                        return Err(parse::Error::new(
+                            lit.span(),
+                            format!("this literal must be in the range 1...{}", u32::MAX),
+                        ));
+                    }
+
+                    tick_hz = value;
+                }
+
+                "bits" => {
+                    if bits.is_some() {
+                        return Err(parse::Error::new(
+                            ident.span(),
+                            "argument appears more than once",
+                        ));
+                    }
+
+                    // #lit
+                    let lit: LitInt = content.parse()?;
+
+                    if !lit.suffix().is_empty() {
+                        return Err(parse::Error::new(
+                            lit.span(),
+                            "this literal must be unsuffixed",
+                        ));
+                    }
+
+                    let value = lit.base10_parse::<u8>().ok();
+                    if !matches!(value, Some(8) | Some(16) | Some(32) | Some(64)) {
+                        return Err(parse::Error::new(
+                            lit.span(),
+                            "this literal must be one of 8, 16, 32 or 64",
+                        ));
+                    }
+
+                    bits = value;
+                }
+
                 _ => {
-                    return Err(parse::Error::new(ident.span(), "unexpected argument"));
+                    return Err(util::unexpected_argument(
+                        &ident,
+                        &["binds", "priority", "default", "tick_hz", "bits"],
+                    ));
                 }
             }
 
@@ -470,13 +824,123 @@ fn monotonic_args(tokens: TokenStream2) -> parse::Result<MonotonicArgs> {
                 "`binds = ...` is missing",
             ));
         };
+        check_fixed_priority(&binds, priority_span, settings)?;
         let default = default.unwrap_or(false);
 
         Ok(MonotonicArgs {
             binds,
             priority,
             default,
+            tick_hz,
+            bits,
         })
     })
     .parse2(tokens)
 }
+
+fn channel_args(tokens: TokenStream2) -> parse::Result<MessageChannelArgs> {
+    (|input: ParseStream<'_>| -> parse::Result<MessageChannelArgs> {
+        let mut capacity = None;
+
+        let content;
+        parenthesized!(content in input);
+        loop {
+            if content.is_empty() {
+                break;
+            }
+
+            // #ident = ..
+            let ident: Ident = content.parse()?;
+            let _: Token![=] = content.parse()?;
+
+            let ident_s = ident.to_string();
+            match &*ident_s {
+                "capacity" => {
+                    if capacity.is_some() {
+                        return Err(parse::Error::new(
+                            ident.span(),
+                            "argument appears more than once",
+                        ));
+                    }
+
+                    // #lit
+                    let lit: LitInt = content.parse()?;
+
+                    if !lit.suffix().is_empty() {
+                        return Err(parse::Error::new(
+                            lit.span(),
+                            "this literal must be unsuffixed",
+                        ));
+                    }
+
+                    let value = lit.base10_parse::<u8>().ok();
+                    if value.is_none() || value == Some(0) {
+                        return Err(parse::Error::new(
+                            lit.span(),
+                            "this literal must be in the range 1...255",
+                        ));
+                    }
+
+                    capacity = value;
+                }
+
+                _ => {
+                    return Err(util::unexpected_argument(&ident, &["capacity"]));
+                }
+            }
+
+            if content.is_empty() {
+                break;
+            }
+
+            // ,
+            let _: Token![,] = content.parse()?;
+        }
+        let capacity = if let Some(capacity) = capacity {
+            capacity
+        } else {
+            return Err(parse::Error::new(
+                content.span(),
+                "`capacity = ...` is missing",
+            ));
+        };
+
+        Ok(MessageChannelArgs { capacity })
+    })
+    .parse2(tokens)
+}
+
+fn priority_out_of_range(span: Span, settings: &Settings) -> parse::Error {
+    let priorities = settings.target.profile().priorities;
+
+    parse::Error::new(
+        span,
+        format!(
+            "this literal must be in the range {}...{}",
+            priorities.start(),
+            priorities.end()
+        ),
+    )
+}
+
+// Checks that no priority is given to a vector whose priority is fixed by the architecture
+fn check_fixed_priority(
+    binds: &Ident,
+    priority: Option<Span>,
+    settings: &Settings,
+) -> parse::Result<()> {
+    if let (Some(exception), Some(span)) = (settings.target.exception(&binds.to_string()), priority)
+    {
+        if !exception.is_configurable() {
+            return Err(parse::Error::new(
+                span,
+                format!(
+                    "the priority of the `{}` exception is fixed and can't be configured",
+                    exception.name
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
@@ -5,15 +5,19 @@ use proc_macro2::TokenStream as TokenStream2;
 use syn::{
     parse::{self, ParseStream, Parser},
     spanned::Spanned,
-    Expr, ExprArray, Fields, ForeignItem, Ident, Item, LitBool, Path, Token, Type, Visibility,
+    Expr, ExprArray, Fields, ForeignItem, Ident, Item, ItemUse, LitBool, LitStr, Path, Token,
+    Visibility,
 };
 
 use super::Input;
 use crate::{
     ast::{
         App, AppArgs, ExternInterrupt, ExternInterrupts, HardwareTask, Idle, IdleArgs, Init,
-        InitArgs, LocalResource, Monotonic, MonotonicArgs, SharedResource, SoftwareTask,
+        InitArgs, LocalResource, MessageChannel, MessageChannelArgs, Monotonic, MonotonicArgs,
+        PriorityAssignment, SharedResource, SoftwareTask,
     },
+    device,
+    normalize::Imports,
     parse::util,
     Either, Map, Set, Settings,
 };
@@ -25,6 +29,7 @@ impl AppArgs {
             let mut device = None;
             let mut peripherals = true;
             let mut extern_interrupts = ExternInterrupts::new();
+            let mut priorities = PriorityAssignment::Manual;
 
             loop {
                 if input.is_empty() {
@@ -112,8 +117,25 @@ impl AppArgs {
                             ));
                         }
                     }
+                    "priorities" => {
+                        let lit: LitStr = input.parse()?;
+
+                        priorities = match &*lit.value() {
+                            "manual" => PriorityAssignment::Manual,
+                            "deadline-monotonic" => PriorityAssignment::DeadlineMonotonic,
+                            _ => {
+                                return Err(parse::Error::new(
+                                    lit.span(),
+                                    "expected `\"manual\"` or `\"deadline-monotonic\"`",
+                                ));
+                            }
+                        };
+                    }
                     _ => {
-                        return Err(parse::Error::new(ident.span(), "unexpected argument"));
+                        return Err(util::unexpected_argument(
+                            &ident,
+                            &["device", "peripherals", "dispatchers", "priorities"],
+                        ));
                     }
                 }
 
@@ -129,6 +151,7 @@ impl AppArgs {
                 device,
                 peripherals,
                 extern_interrupts,
+                priorities,
             })
         })
         .parse2(tokens)
@@ -137,397 +160,535 @@ impl AppArgs {
 
 impl App {
     pub(crate) fn parse(args: AppArgs, input: Input, settings: &Settings) -> parse::Result<Self> {
-        let mut init = None;
-        let mut idle = None;
-
-        let mut shared_resources_ident = None;
-        let mut shared_resources = Map::new();
-        let mut local_resources_ident = None;
-        let mut local_resources = Map::new();
-        let mut monotonics = Map::new();
-        let mut hardware_tasks = Map::new();
-        let mut software_tasks = Map::new();
-        let mut user_imports = vec![];
-        let mut user_code = vec![];
-
-        let mut seen_idents = HashSet::<Ident>::new();
-        let mut bindings = HashSet::<Ident>::new();
-        let mut monotonic_types = HashSet::<Type>::new();
-
-        let mut check_binding = |ident: &Ident| {
-            if bindings.contains(ident) {
-                return Err(parse::Error::new(
-                    ident.span(),
-                    "this interrupt is already bound",
-                ));
-            } else {
-                bindings.insert(ident.clone());
+        Self::parse_recovering(args, input, settings, None)
+    }
+
+    // When `errors` is provided, items that fail to parse are reported there and skipped
+    pub(crate) fn parse_recovering(
+        args: AppArgs,
+        input: Input,
+        settings: &Settings,
+        mut errors: Option<&mut Vec<parse::Error>>,
+    ) -> parse::Result<Self> {
+        let mut items = Items::new(settings);
+
+        for item in input.items {
+            if let Err(e) = items.parse(item) {
+                if let Some(errors) = errors.as_mut() {
+                    errors.push(e);
+                } else {
+                    return Err(e);
+                }
             }
+        }
 
-            Ok(())
-        };
+        let Items {
+            init,
+            has_init,
+            idle,
+            shared_resources_ident,
+            shared_resources,
+            local_resources_ident,
+            local_resources,
+            monotonics,
+            message_channels,
+            hardware_tasks,
+            software_tasks,
+            user_imports,
+            user_code,
+            ..
+        } = items;
 
-        let mut check_ident = |ident: &Ident| {
-            if seen_idents.contains(ident) {
-                return Err(parse::Error::new(
-                    ident.span(),
-                    "this identifier has already been used",
-                ));
+        let mut report = |e: parse::Error| {
+            if let Some(errors) = errors.as_mut() {
+                errors.push(e);
+                Ok(())
             } else {
-                seen_idents.insert(ident.clone());
+                Err(e)
             }
-
-            Ok(())
         };
 
-        let mut check_monotonic = |ty: &Type| {
-            if monotonic_types.contains(ty) {
-                return Err(parse::Error::new(
-                    ty.span(),
+        // Check that no two monotonics use the same type, however it's written; `use` items may
+        // come after the monotonics so this can only be checked once every item has been parsed
+        let imports = Imports::new(&user_imports);
+        let mut monotonic_types = HashSet::new();
+        for monotonic in monotonics.values() {
+            if !monotonic_types.insert(imports.normalize(&monotonic.ty)) {
+                report(parse::Error::new(
+                    monotonic.ty.span(),
                     "this type is already used by another monotonic",
-                ));
-            } else {
-                monotonic_types.insert(ty.clone());
+                ))?;
             }
+        }
 
-            Ok(())
+        // In recovering mode a missing or broken `#[init]` is replaced by a placeholder so that
+        // the rest of the app can still be checked
+        let init = if let Some(init) = init {
+            init
+        } else {
+            if !has_init {
+                report(parse::Error::new(
+                    input.ident.span(),
+                    "no `#[init]` function defined",
+                ))?;
+            }
+
+            Init::placeholder(
+                input.ident.span(),
+                shared_resources_ident.clone(),
+                local_resources_ident.clone(),
+            )
         };
 
-        for mut item in input.items {
-            match item {
-                Item::Fn(mut item) => {
-                    let span = item.sig.ident.span();
-                    if let Some(pos) = item
-                        .attrs
-                        .iter()
-                        .position(|attr| util::attr_eq(attr, "init"))
-                    {
-                        let args = InitArgs::parse(item.attrs.remove(pos).tokens)?;
+        if let Some(shared_resources_ident) = shared_resources_ident {
+            if shared_resources_ident != init.user_shared_struct {
+                report(parse::Error::new(
+                    init.user_shared_struct.span(),
+                    format!(
+                        "This name and the one defined on `#[shared]` are not the same. Should this be `{}`?",
+                        shared_resources_ident
+                    ),
+                ))?;
+            }
+        } else {
+            report(parse::Error::new(
+                input.ident.span(),
+                "no `#[shared]` resource struct defined",
+            ))?;
+        }
 
-                        // If an init function already exists, error
-                        if init.is_some() {
-                            return Err(parse::Error::new(
-                                span,
-                                "`#[init]` function must appear at most once",
-                            ));
-                        }
+        if let Some(local_resources_ident) = local_resources_ident {
+            if local_resources_ident != init.user_local_struct {
+                report(parse::Error::new(
+                    init.user_local_struct.span(),
+                    format!(
+                        "This name and the one defined on `#[local]` are not the same. Should this be `{}`?",
+                        local_resources_ident
+                    ),
+                ))?;
+            }
+        } else {
+            report(parse::Error::new(
+                input.ident.span(),
+                "no `#[local]` resource struct defined",
+            ))?;
+        }
 
-                        check_ident(&item.sig.ident)?;
+        Ok(App {
+            args,
+            name: input.ident,
+            init,
+            idle,
+            monotonics,
+            message_channels,
+            shared_resources,
+            local_resources,
+            user_imports,
+            user_code,
+            hardware_tasks,
+            software_tasks,
+        })
+    }
+}
 
-                        init = Some(Init::parse(args, item)?);
-                    } else if let Some(pos) = item
-                        .attrs
-                        .iter()
-                        .position(|attr| util::attr_eq(attr, "idle"))
-                    {
-                        let args = IdleArgs::parse(item.attrs.remove(pos).tokens)?;
+// The items of the `#[app]` module parsed so far
+struct Items<'a> {
+    settings: &'a Settings,
+
+    init: Option<Init>,
+    // whether an `#[init]` function was found, even if it failed to parse
+    has_init: bool,
+    idle: Option<Idle>,
+
+    shared_resources_ident: Option<Ident>,
+    shared_resources: Map<SharedResource>,
+    local_resources_ident: Option<Ident>,
+    local_resources: Map<LocalResource>,
+    monotonics: Map<Monotonic>,
+    message_channels: Map<MessageChannel>,
+    hardware_tasks: Map<HardwareTask>,
+    software_tasks: Map<SoftwareTask>,
+    user_imports: Vec<ItemUse>,
+    user_code: Vec<Item>,
+
+    seen_idents: HashSet<Ident>,
+    bindings: HashSet<Ident>,
+}
 
-                        // If an idle function already exists, error
-                        if idle.is_some() {
-                            return Err(parse::Error::new(
-                                span,
-                                "`#[idle]` function must appear at most once",
-                            ));
-                        }
+impl<'a> Items<'a> {
+    fn new(settings: &'a Settings) -> Self {
+        Items {
+            settings,
+            init: None,
+            has_init: false,
+            idle: None,
+            shared_resources_ident: None,
+            shared_resources: Map::new(),
+            local_resources_ident: None,
+            local_resources: Map::new(),
+            monotonics: Map::new(),
+            message_channels: Map::new(),
+            hardware_tasks: Map::new(),
+            software_tasks: Map::new(),
+            user_imports: vec![],
+            user_code: vec![],
+            seen_idents: HashSet::new(),
+            bindings: HashSet::new(),
+        }
+    }
 
-                        check_ident(&item.sig.ident)?;
+    fn check_binding(&mut self, ident: &Ident) -> parse::Result<()> {
+        device::check(ident, self.settings)?;
 
-                        idle = Some(Idle::parse(args, item)?);
-                    } else if let Some(pos) = item
-                        .attrs
-                        .iter()
-                        .position(|attr| util::attr_eq(attr, "task"))
-                    {
-                        if hardware_tasks.contains_key(&item.sig.ident)
-                            || software_tasks.contains_key(&item.sig.ident)
-                        {
-                            return Err(parse::Error::new(
-                                span,
-                                "this task is defined multiple times",
-                            ));
-                        }
+        let exception = self.settings.target.exception(&ident.to_string());
+        if exception.map(|e| !e.bindable).unwrap_or(false) {
+            return Err(parse::Error::new(
+                ident.span(),
+                "this exception can't be bound to a task or monotonic",
+            ));
+        }
+
+        if self.bindings.contains(ident) {
+            return Err(parse::Error::new(
+                ident.span(),
+                if exception.is_some() {
+                    "this exception is already bound; it can't be used by both a monotonic and a task"
+                } else {
+                    "this interrupt is already bound"
+                },
+            ));
+        } else {
+            self.bindings.insert(ident.clone());
+        }
 
-                        match crate::parse::task_args(item.attrs.remove(pos).tokens, settings)? {
-                            Either::Left(args) => {
-                                check_binding(&args.binds)?;
-                                check_ident(&item.sig.ident)?;
+        Ok(())
+    }
 
-                                hardware_tasks.insert(
-                                    item.sig.ident.clone(),
-                                    HardwareTask::parse(args, item)?,
-                                );
-                            }
+    fn check_ident(&mut self, ident: &Ident) -> parse::Result<()> {
+        if self.seen_idents.contains(ident) {
+            return Err(parse::Error::new(
+                ident.span(),
+                "this identifier has already been used",
+            ));
+        } else {
+            self.seen_idents.insert(ident.clone());
+        }
 
-                            Either::Right(args) => {
-                                check_ident(&item.sig.ident)?;
+        Ok(())
+    }
 
-                                software_tasks.insert(
-                                    item.sig.ident.clone(),
-                                    SoftwareTask::parse(args, item)?,
-                                );
-                            }
-                        }
-                    } else {
-                        // Forward normal functions
-                        user_code.push(Item::Fn(item.clone()));
+    fn parse(&mut self, mut item: Item) -> parse::Result<()> {
+        match item {
+            Item::Fn(mut item) => {
+                let span = item.sig.ident.span();
+                if let Some(pos) = item
+                    .attrs
+                    .iter()
+                    .position(|attr| util::attr_eq(attr, "init"))
+                {
+                    self.has_init = true;
+                    let args = InitArgs::parse(item.attrs.remove(pos).tokens)?;
+
+                    // If an init function already exists, error
+                    if self.init.is_some() {
+                        return Err(parse::Error::new(
+                            span,
+                            "`#[init]` function must appear at most once",
+                        ));
                     }
-                }
 
-                Item::Struct(ref mut struct_item) => {
-                    // Match structures with the attribute #[shared], name of structure is not
-                    // important
-                    if let Some(_pos) = struct_item
-                        .attrs
-                        .iter()
-                        .position(|attr| util::attr_eq(attr, "shared"))
-                    {
-                        let span = struct_item.ident.span();
+                    self.check_ident(&item.sig.ident)?;
 
-                        shared_resources_ident = Some(struct_item.ident.clone());
+                    self.init = Some(Init::parse(args, item)?);
+                } else if let Some(pos) = item
+                    .attrs
+                    .iter()
+                    .position(|attr| util::attr_eq(attr, "idle"))
+                {
+                    let args = IdleArgs::parse(item.attrs.remove(pos).tokens)?;
 
-                        if !shared_resources.is_empty() {
-                            return Err(parse::Error::new(
-                                span,
-                                "`#[shared]` struct must appear at most once",
-                            ));
-                        }
+                    // If an idle function already exists, error
+                    if self.idle.is_some() {
+                        return Err(parse::Error::new(
+                            span,
+                            "`#[idle]` function must appear at most once",
+                        ));
+                    }
 
-                        if struct_item.vis != Visibility::Inherited {
-                            return Err(parse::Error::new(
-                                struct_item.span(),
-                                "this item must have inherited / private visibility",
-                            ));
-                        }
+                    self.check_ident(&item.sig.ident)?;
 
-                        if let Fields::Named(fields) = &mut struct_item.fields {
-                            for field in &mut fields.named {
-                                let ident = field.ident.as_ref().expect("UNREACHABLE");
+                    self.idle = Some(Idle::parse(args, item)?);
+                } else if let Some(pos) = item
+                    .attrs
+                    .iter()
+                    .position(|attr| util::attr_eq(attr, "task"))
+                {
+                    if self.hardware_tasks.contains_key(&item.sig.ident)
+                        || self.software_tasks.contains_key(&item.sig.ident)
+                    {
+                        return Err(parse::Error::new(
+                            span,
+                            "this task is defined multiple times",
+                        ));
+                    }
 
-                                if shared_resources.contains_key(ident) {
-                                    return Err(parse::Error::new(
-                                        ident.span(),
-                                        "this resource is listed more than once",
-                                    ));
-                                }
+                    match crate::parse::task_args(item.attrs.remove(pos).tokens, self.settings)? {
+                        Either::Left(args) => {
+                            self.check_binding(&args.binds)?;
+                            self.check_ident(&item.sig.ident)?;
 
-                                shared_resources.insert(
-                                    ident.clone(),
-                                    SharedResource::parse(field, ident.span())?,
-                                );
-                            }
-                        } else {
-                            return Err(parse::Error::new(
-                                struct_item.span(),
-                                "this `struct` must have named fields",
-                            ));
+                            self.hardware_tasks
+                                .insert(item.sig.ident.clone(), HardwareTask::parse(args, item)?);
                         }
-                    } else if let Some(_pos) = struct_item
-                        .attrs
-                        .iter()
-                        .position(|attr| util::attr_eq(attr, "local"))
-                    {
-                        let span = struct_item.ident.span();
 
-                        local_resources_ident = Some(struct_item.ident.clone());
+                        Either::Right(args) => {
+                            self.check_ident(&item.sig.ident)?;
 
-                        if !local_resources.is_empty() {
-                            return Err(parse::Error::new(
-                                span,
-                                "`#[local]` struct must appear at most once",
-                            ));
+                            self.software_tasks
+                                .insert(item.sig.ident.clone(), SoftwareTask::parse(args, item)?);
                         }
+                    }
+                } else {
+                    // Forward normal functions
+                    self.user_code.push(Item::Fn(item.clone()));
+                }
+            }
 
-                        if struct_item.vis != Visibility::Inherited {
-                            return Err(parse::Error::new(
-                                struct_item.span(),
-                                "this item must have inherited / private visibility",
-                            ));
-                        }
+            Item::Struct(ref mut struct_item) => {
+                // Match structures with the attribute #[shared], name of structure is not
+                // important
+                if let Some(_pos) = struct_item
+                    .attrs
+                    .iter()
+                    .position(|attr| util::attr_eq(attr, "shared"))
+                {
+                    let span = struct_item.ident.span();
 
-                        if let Fields::Named(fields) = &mut struct_item.fields {
-                            for field in &mut fields.named {
-                                let ident = field.ident.as_ref().expect("UNREACHABLE");
+                    self.shared_resources_ident = Some(struct_item.ident.clone());
 
-                                if local_resources.contains_key(ident) {
-                                    return Err(parse::Error::new(
-                                        ident.span(),
-                                        "this resource is listed more than once",
-                                    ));
-                                }
+                    if !self.shared_resources.is_empty() {
+                        return Err(parse::Error::new(
+                            span,
+                            "`#[shared]` struct must appear at most once",
+                        ));
+                    }
+
+                    if struct_item.vis != Visibility::Inherited {
+                        return Err(parse::Error::new(
+                            struct_item.span(),
+                            "this item must have inherited / private visibility",
+                        ));
+                    }
+
+                    if let Fields::Named(fields) = &mut struct_item.fields {
+                        for field in &mut fields.named {
+                            let ident = field.ident.as_ref().expect("UNREACHABLE");
 
-                                local_resources.insert(
-                                    ident.clone(),
-                                    LocalResource::parse(field, ident.span())?,
-                                );
+                            if self.shared_resources.contains_key(ident) {
+                                return Err(parse::Error::new(
+                                    ident.span(),
+                                    "this resource is listed more than once",
+                                ));
                             }
-                        } else {
-                            return Err(parse::Error::new(
-                                struct_item.span(),
-                                "this `struct` must have named fields",
-                            ));
+
+                            self.shared_resources
+                                .insert(ident.clone(), SharedResource::parse(field, ident.span())?);
                         }
                     } else {
-                        // Structure without the #[resources] attribute should just be passed along
-                        user_code.push(item.clone());
+                        return Err(parse::Error::new(
+                            struct_item.span(),
+                            "this `struct` must have named fields",
+                        ));
                     }
-                }
+                } else if let Some(_pos) = struct_item
+                    .attrs
+                    .iter()
+                    .position(|attr| util::attr_eq(attr, "local"))
+                {
+                    let span = struct_item.ident.span();
+
+                    self.local_resources_ident = Some(struct_item.ident.clone());
 
-                Item::ForeignMod(mod_) => {
-                    if !util::abi_is_rust(&mod_.abi) {
+                    if !self.local_resources.is_empty() {
                         return Err(parse::Error::new(
-                            mod_.abi.extern_token.span(),
-                            "this `extern` block must use the \"Rust\" ABI",
+                            span,
+                            "`#[local]` struct must appear at most once",
                         ));
                     }
 
-                    for item in mod_.items {
-                        if let ForeignItem::Fn(mut item) = item {
-                            let span = item.sig.ident.span();
-                            if let Some(pos) = item
-                                .attrs
-                                .iter()
-                                .position(|attr| util::attr_eq(attr, "task"))
-                            {
-                                if hardware_tasks.contains_key(&item.sig.ident)
-                                    || software_tasks.contains_key(&item.sig.ident)
-                                {
-                                    return Err(parse::Error::new(
-                                        span,
-                                        "this task is defined multiple times",
-                                    ));
-                                }
+                    if struct_item.vis != Visibility::Inherited {
+                        return Err(parse::Error::new(
+                            struct_item.span(),
+                            "this item must have inherited / private visibility",
+                        ));
+                    }
 
-                                if item.attrs.len() != 1 {
-                                    return Err(parse::Error::new(
-                                        span,
-                                        "`extern` task required `#[task(..)]` attribute",
-                                    ));
-                                }
+                    if let Fields::Named(fields) = &mut struct_item.fields {
+                        for field in &mut fields.named {
+                            let ident = field.ident.as_ref().expect("UNREACHABLE");
 
-                                match crate::parse::task_args(
-                                    item.attrs.remove(pos).tokens,
-                                    settings,
-                                )? {
-                                    Either::Left(args) => {
-                                        check_binding(&args.binds)?;
-                                        check_ident(&item.sig.ident)?;
-
-                                        hardware_tasks.insert(
-                                            item.sig.ident.clone(),
-                                            HardwareTask::parse_foreign(args, item)?,
-                                        );
-                                    }
+                            if self.local_resources.contains_key(ident) {
+                                return Err(parse::Error::new(
+                                    ident.span(),
+                                    "this resource is listed more than once",
+                                ));
+                            }
 
-                                    Either::Right(args) => {
-                                        check_ident(&item.sig.ident)?;
+                            self.local_resources
+                                .insert(ident.clone(), LocalResource::parse(field, ident.span())?);
+                        }
+                    } else {
+                        return Err(parse::Error::new(
+                            struct_item.span(),
+                            "this `struct` must have named fields",
+                        ));
+                    }
+                } else {
+                    // Structure without the #[resources] attribute should just be passed along
+                    self.user_code.push(item.clone());
+                }
+            }
 
-                                        software_tasks.insert(
-                                            item.sig.ident.clone(),
-                                            SoftwareTask::parse_foreign(args, item)?,
-                                        );
-                                    }
-                                }
-                            } else {
+            Item::ForeignMod(mod_) => {
+                if !util::abi_is_rust(&mod_.abi) {
+                    return Err(parse::Error::new(
+                        mod_.abi.extern_token.span(),
+                        "this `extern` block must use the \"Rust\" ABI",
+                    ));
+                }
+
+                for item in mod_.items {
+                    if let ForeignItem::Fn(mut item) = item {
+                        let span = item.sig.ident.span();
+                        if let Some(pos) = item
+                            .attrs
+                            .iter()
+                            .position(|attr| util::attr_eq(attr, "task"))
+                        {
+                            if self.hardware_tasks.contains_key(&item.sig.ident)
+                                || self.software_tasks.contains_key(&item.sig.ident)
+                            {
+                                return Err(parse::Error::new(
+                                    span,
+                                    "this task is defined multiple times",
+                                ));
+                            }
+
+                            if item.attrs.len() != 1 {
                                 return Err(parse::Error::new(
                                     span,
                                     "`extern` task required `#[task(..)]` attribute",
                                 ));
                             }
+
+                            match crate::parse::task_args(
+                                item.attrs.remove(pos).tokens,
+                                self.settings,
+                            )? {
+                                Either::Left(args) => {
+                                    self.check_binding(&args.binds)?;
+                                    self.check_ident(&item.sig.ident)?;
+
+                                    self.hardware_tasks.insert(
+                                        item.sig.ident.clone(),
+                                        HardwareTask::parse_foreign(args, item)?,
+                                    );
+                                }
+
+                                Either::Right(args) => {
+                                    self.check_ident(&item.sig.ident)?;
+
+                                    self.software_tasks.insert(
+                                        item.sig.ident.clone(),
+                                        SoftwareTask::parse_foreign(args, item)?,
+                                    );
+                                }
+                            }
                         } else {
                             return Err(parse::Error::new(
-                                item.span(),
-                                "this item must live outside the `#[app]` module",
+                                span,
+                                "`extern` task required `#[task(..)]` attribute",
                             ));
                         }
+                    } else {
+                        return Err(parse::Error::new(
+                            item.span(),
+                            "this item must live outside the `#[app]` module",
+                        ));
                     }
                 }
-                Item::Use(itemuse_) => {
-                    // Store the user provided use-statements
-                    user_imports.push(itemuse_.clone());
-                }
-                Item::Type(ref mut type_item) => {
-                    // Match types with the attribute #[monotonic]
-                    if let Some(pos) = type_item
-                        .attrs
-                        .iter()
-                        .position(|attr| util::attr_eq(attr, "monotonic"))
-                    {
-                        let span = type_item.ident.span();
-
-                        if monotonics.contains_key(&type_item.ident) {
-                            return Err(parse::Error::new(
-                                span,
-                                "`#[monotonic]` on a specific type must appear at most once",
-                            ));
-                        }
-
-                        if type_item.vis != Visibility::Inherited {
-                            return Err(parse::Error::new(
-                                type_item.span(),
-                                "this item must have inherited / private visibility",
-                            ));
-                        }
+            }
+            Item::Use(itemuse_) => {
+                // Store the user provided use-statements
+                self.user_imports.push(itemuse_.clone());
+            }
+            Item::Type(ref mut type_item) => {
+                // Match types with the attribute #[monotonic]
+                if let Some(pos) = type_item
+                    .attrs
+                    .iter()
+                    .position(|attr| util::attr_eq(attr, "monotonic"))
+                {
+                    let span = type_item.ident.span();
+
+                    if self.monotonics.contains_key(&type_item.ident) {
+                        return Err(parse::Error::new(
+                            span,
+                            "`#[monotonic]` on a specific type must appear at most once",
+                        ));
+                    }
 
-                        check_monotonic(&*type_item.ty)?;
+                    if type_item.vis != Visibility::Inherited {
+                        return Err(parse::Error::new(
+                            type_item.span(),
+                            "this item must have inherited / private visibility",
+                        ));
+                    }
 
-                        let args = MonotonicArgs::parse(type_item.attrs.remove(pos).tokens)?;
+                    let args =
+                        MonotonicArgs::parse(type_item.attrs.remove(pos).tokens, self.settings)?;
 
-                        check_binding(&args.binds)?;
+                    self.check_binding(&args.binds)?;
 
-                        let monotonic = Monotonic::parse(args, type_item, span)?;
+                    let monotonic = Monotonic::parse(args, type_item, span)?;
 
-                        monotonics.insert(type_item.ident.clone(), monotonic);
+                    self.monotonics.insert(type_item.ident.clone(), monotonic);
+                } else if let Some(pos) = type_item
+                    .attrs
+                    .iter()
+                    .position(|attr| util::attr_eq(attr, "channel"))
+                {
+                    if self.message_channels.contains_key(&type_item.ident) {
+                        return Err(parse::Error::new(
+                            type_item.ident.span(),
+                            "`#[channel]` on a specific type must appear at most once",
+                        ));
                     }
 
-                    // All types are passed on
-                    user_code.push(item.clone());
-                }
-                _ => {
-                    // Anything else within the module should not make any difference
-                    user_code.push(item.clone());
-                }
-            }
-        }
+                    if type_item.vis != Visibility::Inherited {
+                        return Err(parse::Error::new(
+                            type_item.span(),
+                            "this item must have inherited / private visibility",
+                        ));
+                    }
 
-        let shared_resources_ident =
-            shared_resources_ident.expect("No `#[shared]` resource struct defined");
-        let local_resources_ident =
-            local_resources_ident.expect("No `#[local]` resource struct defined");
-        let init = init.expect("No `#[init]` function defined");
+                    let args = MessageChannelArgs::parse(type_item.attrs.remove(pos).tokens)?;
+                    let channel = MessageChannel::parse(args, type_item)?;
 
-        if shared_resources_ident != init.user_shared_struct {
-            return Err(parse::Error::new(
-                init.user_shared_struct.span(),
-                format!(
-                    "This name and the one defined on `#[shared]` are not the same. Should this be `{}`?",
-                    shared_resources_ident
-                ),
-            ));
-        }
+                    self.message_channels
+                        .insert(type_item.ident.clone(), channel);
+                }
 
-        if local_resources_ident != init.user_local_struct {
-            return Err(parse::Error::new(
-                init.user_local_struct.span(),
-                format!(
-                    "This name and the one defined on `#[local]` are not the same. Should this be `{}`?",
-                    local_resources_ident
-                ),
-            ));
+                // All types are passed on
+                self.user_code.push(item.clone());
+            }
+            _ => {
+                // Anything else within the module should not make any difference
+                self.user_code.push(item.clone());
+            }
         }
 
-        Ok(App {
-            args,
-            name: input.ident,
-            init,
-            idle,
-            monotonics,
-            shared_resources,
-            local_resources,
-            user_imports,
-            user_code,
-            hardware_tasks,
-            software_tasks,
-        })
+        Ok(())
     }
 }
 
@@ -0,0 +1,33 @@
+use proc_macro2::TokenStream as TokenStream2;
+use syn::{parse, spanned::Spanned, ItemType};
+
+use crate::{
+    ast::{MessageChannel, MessageChannelArgs},
+    parse::util,
+};
+
+impl MessageChannelArgs {
+    pub(crate) fn parse(tokens: TokenStream2) -> parse::Result<Self> {
+        crate::parse::channel_args(tokens)
+    }
+}
+
+impl MessageChannel {
+    pub(crate) fn parse(args: MessageChannelArgs, item: &ItemType) -> parse::Result<Self> {
+        let (cfgs, attrs) = util::extract_cfgs(item.attrs.clone());
+
+        if !attrs.is_empty() {
+            return Err(parse::Error::new(
+                attrs[0].path.span(),
+                "Channel does not support attributes other than `#[cfg]`",
+            ));
+        }
+
+        Ok(MessageChannel {
+            cfgs,
+            ident: item.ident.clone(),
+            ty: item.ty.clone(),
+            args,
+        })
+    }
+}
@@ -1,6 +1,6 @@
-use proc_macro2::TokenStream as TokenStream2;
+use proc_macro2::{Span, TokenStream as TokenStream2};
 
-use syn::{parse, ItemFn};
+use syn::{parse, parse_quote, Ident, ItemFn};
 
 use crate::{
     ast::{Init, InitArgs},
@@ -43,10 +43,27 @@ impl Init {
 
         Err(parse::Error::new(
             span,
-            &format!(
+            format!(
                 "the `#[init]` function must have signature `fn({}::Context) -> (Shared resources struct, Local resources struct, {0}::Monotonics)`",
                 name
             ),
         ))
     }
+
+    // Stands in for a missing or broken `#[init]` function when recovering from errors
+    pub(crate) fn placeholder(
+        span: Span,
+        user_shared_struct: Option<Ident>,
+        user_local_struct: Option<Ident>,
+    ) -> Self {
+        Init {
+            args: InitArgs::default(),
+            attrs: vec![],
+            name: Ident::new("init", span),
+            context: Box::new(parse_quote!(_)),
+            stmts: vec![],
+            user_shared_struct: user_shared_struct.unwrap_or_else(|| Ident::new("Shared", span)),
+            user_local_struct: user_local_struct.unwrap_or_else(|| Ident::new("Local", span)),
+        }
+    }
 }
@@ -5,11 +5,12 @@ use syn::{parse, spanned::Spanned, ItemType, Visibility};
 use crate::{
     ast::{Monotonic, MonotonicArgs},
     parse::util,
+    Settings,
 };
 
 impl MonotonicArgs {
-    pub(crate) fn parse(tokens: TokenStream2) -> parse::Result<Self> {
-        crate::parse::monotonic_args(tokens)
+    pub(crate) fn parse(tokens: TokenStream2, settings: &Settings) -> parse::Result<Self> {
+        crate::parse::monotonic_args(tokens, settings)
     }
 }
 
@@ -24,7 +25,7 @@ impl Monotonic {
 
         let (cfgs, attrs) = util::extract_cfgs(item.attrs.clone());
 
-        if attrs.len() > 0 {
+        if !attrs.is_empty() {
             return Err(parse::Error::new(
                 attrs[0].path.span(),
                 "Monotonic does not support attributes other than `#[cfg]`",
@@ -1,5 +1,5 @@
 use proc_macro2::Span;
-use syn::{parse, Field, Visibility};
+use syn::{parse, spanned::Spanned, Field, Visibility};
 
 use crate::{
     ast::{LocalResource, SharedResource, SharedResourceProperties},
@@ -18,6 +18,7 @@ impl SharedResource {
         let (cfgs, mut attrs) = util::extract_cfgs(item.attrs.clone());
 
         let lock_free = util::extract_lock_free(&mut attrs)?;
+        util::check_attrs(&attrs, &["lock_free"])?;
 
         Ok(SharedResource {
             cfgs,
@@ -39,6 +40,15 @@ impl LocalResource {
 
         let (cfgs, attrs) = util::extract_cfgs(item.attrs.clone());
 
+        if let Some(attr) = attrs.iter().find(|attr| util::attr_eq(attr, "lock_free")) {
+            return Err(parse::Error::new(
+                attr.path.span(),
+                "`#[lock_free]` can only be used on shared resources",
+            ));
+        }
+        // catches misspelled `#[lock_free]`s, which would otherwise be forwarded
+        util::check_attrs(&attrs, &["lock_free"])?;
+
         Ok(LocalResource {
             cfgs,
             attrs,
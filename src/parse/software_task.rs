@@ -1,4 +1,7 @@
-use syn::{parse, ForeignItemFn, ItemFn, Stmt};
+use syn::{
+    parse, spanned::Spanned, ForeignItemFn, GenericArgument, ItemFn, Lifetime, Pat, PatType,
+    PathArguments, Stmt, Type, TypeParamBound,
+};
 
 use crate::{
     ast::{SoftwareTask, SoftwareTaskArgs},
@@ -16,6 +19,8 @@ impl SoftwareTask {
 
         if valid_signature {
             if let Some((context, Ok(inputs))) = util::parse_inputs(item.sig.inputs, &name) {
+                check_inputs(&inputs)?;
+
                 let (cfgs, attrs) = util::extract_cfgs(item.attrs);
 
                 return Ok(SoftwareTask {
@@ -32,7 +37,7 @@ impl SoftwareTask {
 
         Err(parse::Error::new(
             span,
-            &format!(
+            format!(
                 "this task handler must have type signature `fn({}::Context, ..)`",
                 name
             ),
@@ -54,6 +59,8 @@ impl SoftwareTask {
 
         if valid_signature {
             if let Some((context, Ok(inputs))) = util::parse_inputs(item.sig.inputs, &name) {
+                check_inputs(&inputs)?;
+
                 let (cfgs, attrs) = util::extract_cfgs(item.attrs);
 
                 return Ok(SoftwareTask {
@@ -70,10 +77,140 @@ impl SoftwareTask {
 
         Err(parse::Error::new(
             span,
-            &format!(
+            format!(
                 "this task handler must have type signature `fn({}::Context, ..)`",
                 name
             ),
         ))
     }
 }
+
+// The inputs of a task make up the messages that are stored in its queue and then handed to it, so
+// their types must be `'static` and sized, and their patterns must bind the whole message
+fn check_inputs(inputs: &[PatType]) -> parse::Result<()> {
+    for input in inputs {
+        check_pat(&input.pat)?;
+        check_type(&input.ty, true)?;
+    }
+
+    Ok(())
+}
+
+fn check_pat(pat: &Pat) -> parse::Result<()> {
+    match pat {
+        Pat::Ident(pat) => match &pat.subpat {
+            Some((_, subpat)) => check_pat(subpat),
+            None => Ok(()),
+        },
+
+        Pat::Wild(_) => Ok(()),
+
+        Pat::Reference(pat) => check_pat(&pat.pat),
+
+        Pat::Tuple(tuple) => tuple.elems.iter().try_for_each(check_pat),
+
+        Pat::TupleStruct(tuple) => tuple.pat.elems.iter().try_for_each(check_pat),
+
+        Pat::Struct(pat) => pat
+            .fields
+            .iter()
+            .try_for_each(|field| check_pat(&field.pat)),
+
+        // `..` inside a tuple or a struct
+        Pat::Rest(_) => Ok(()),
+
+        _ => Err(parse::Error::new(
+            pat.span(),
+            "this pattern can't be used for a task input; use an identifier, `_` or a tuple or struct pattern made of them",
+        )),
+    }
+}
+
+// `value` is set where the type is used directly as a value: the input itself and the elements of
+// tuples and arrays. Elsewhere (behind a reference, a pointer or in a generic argument like
+// `Arc<dyn Trait>`) unsized types are fine and only lifetimes and `impl Trait` are checked
+fn check_type(ty: &Type, value: bool) -> parse::Result<()> {
+    match ty {
+        Type::Array(array) => check_type(&array.elem, true),
+        Type::Group(group) => check_type(&group.elem, value),
+        Type::Paren(paren) => check_type(&paren.elem, value),
+        Type::Ptr(ptr) => check_type(&ptr.elem, false),
+        Type::Tuple(tuple) => tuple
+            .elems
+            .iter()
+            .try_for_each(|elem| check_type(elem, true)),
+
+        Type::Reference(reference) => {
+            match &reference.lifetime {
+                Some(lifetime) => check_lifetime(lifetime)?,
+                None => {
+                    return Err(parse::Error::new(
+                        reference.and_token.span,
+                        "references in task inputs must be `&'static`",
+                    ));
+                }
+            }
+
+            check_type(&reference.elem, false)
+        }
+
+        Type::Path(path) => {
+            if let Some(qself) = &path.qself {
+                check_type(&qself.ty, false)?;
+            }
+
+            for segment in &path.path.segments {
+                if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                    for arg in &args.args {
+                        match arg {
+                            GenericArgument::Lifetime(lifetime) => check_lifetime(lifetime)?,
+                            GenericArgument::Type(ty) => check_type(ty, false)?,
+                            GenericArgument::Binding(binding) => check_type(&binding.ty, false)?,
+                            _ => {}
+                        }
+                    }
+                }
+            }
+
+            Ok(())
+        }
+
+        Type::ImplTrait(_) => Err(parse::Error::new(
+            ty.span(),
+            "`impl Trait` can't be used in task inputs; use a concrete type or `Box<dyn Trait>`",
+        )),
+
+        Type::TraitObject(_) if value => Err(parse::Error::new(
+            ty.span(),
+            "trait objects in task inputs must be boxed: `Box<dyn Trait>`",
+        )),
+        Type::TraitObject(object) => check_bounds(object.bounds.iter()),
+
+        Type::Slice(_) if value => Err(parse::Error::new(
+            ty.span(),
+            "slices in task inputs must be boxed or borrowed: `Box<[T]>` or `&'static [T]`",
+        )),
+        Type::Slice(slice) => check_type(&slice.elem, true),
+
+        _ => Ok(()),
+    }
+}
+
+// Trait objects are `'static` unless a shorter lifetime bound is given
+fn check_bounds<'a>(mut bounds: impl Iterator<Item = &'a TypeParamBound>) -> parse::Result<()> {
+    bounds.try_for_each(|bound| match bound {
+        TypeParamBound::Lifetime(lifetime) => check_lifetime(lifetime),
+        TypeParamBound::Trait(_) => Ok(()),
+    })
+}
+
+fn check_lifetime(lifetime: &Lifetime) -> parse::Result<()> {
+    if lifetime.ident == "static" {
+        Ok(())
+    } else {
+        Err(parse::Error::new(
+            lifetime.span(),
+            "task inputs can't borrow non-`'static` data; use an owned type or a `'static` lifetime",
+        ))
+    }
+}
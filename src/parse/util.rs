@@ -3,13 +3,13 @@ use syn::{
     parse::{self, ParseStream},
     punctuated::Punctuated,
     spanned::Spanned,
-    Abi, AttrStyle, Attribute, Expr, FnArg, ForeignItemFn, Ident, ItemFn, Pat, PatType, Path,
-    PathArguments, ReturnType, Token, Type, Visibility,
+    Abi, AttrStyle, Attribute, Expr, ExprField, FnArg, ForeignItemFn, Ident, ItemFn, Member, Pat,
+    PatType, Path, PathArguments, ReturnType, Token, Type, Visibility,
 };
 
 use crate::{
-    ast::{Access, Local, LocalResources, SharedResources, TaskLocal},
-    Map,
+    ast::{Access, FieldPath, Local, LocalResources, SharedFields, SharedResources, TaskLocal},
+    suggest, Map, Set,
 };
 
 pub fn abi_is_rust(abi: &Abi) -> bool {
@@ -19,6 +19,35 @@ pub fn abi_is_rust(abi: &Abi) -> bool {
     }
 }
 
+/// "unexpected argument" error, suggesting the closest of the `expected` arguments
+pub fn unexpected_argument(ident: &Ident, expected: &[&str]) -> parse::Error {
+    parse::Error::new(
+        ident.span(),
+        suggest::did_you_mean(
+            "unexpected argument",
+            &ident.to_string(),
+            expected.iter().copied(),
+        ),
+    )
+}
+
+/// Rejects attributes that look like a misspelled `rtic` attribute instead of forwarding them
+pub fn check_attrs(attrs: &[Attribute], known: &[&str]) -> parse::Result<()> {
+    for attr in attrs {
+        if let Some(ident) = attr.path.get_ident() {
+            let name = ident.to_string();
+            if let Some(candidate) = suggest::closest(&name, known.iter().copied()) {
+                return Err(parse::Error::new(
+                    ident.span(),
+                    format!("unknown attribute; did you mean `{}`?", candidate),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
 pub fn attr_eq(attr: &Attribute, name: &str) -> bool {
     attr.style == AttrStyle::Outer && attr.path.segments.len() == 1 && {
         let segment = attr.path.segments.first().unwrap();
@@ -82,41 +111,98 @@ pub fn extract_lock_free(attrs: &mut Vec<Attribute>) -> parse::Result<bool> {
     }
 }
 
-pub fn parse_shared_resources(content: ParseStream<'_>) -> parse::Result<SharedResources> {
+pub fn parse_shared_resources(
+    content: ParseStream<'_>,
+) -> parse::Result<(SharedResources, SharedFields)> {
     let inner;
     bracketed!(inner in content);
 
     let mut resources = Map::new();
+    let mut fields = SharedFields::new();
     for e in inner.call(Punctuated::<Expr, Token![,]>::parse_terminated)? {
         let err = Err(parse::Error::new(
             e.span(),
             "identifier appears more than once in list",
         ));
-        let (access, path) = match e {
-            Expr::Path(e) => (Access::Exclusive, e.path),
+        let (access, expr) = match e {
+            Expr::Reference(r) if r.mutability.is_none() => (Access::Shared, *r.expr),
+
+            Expr::Reference(_) => return err,
 
-            Expr::Reference(ref r) if r.mutability.is_none() => match &*r.expr {
-                Expr::Path(e) => (Access::Shared, e.path.clone()),
+            e => (Access::Exclusive, e),
+        };
+
+        let path = match expr {
+            Expr::Path(e) => FieldPath::resource(extract_resource_name_ident(e.path)?),
 
-                _ => return err,
-            },
+            // #resource.#field..
+            Expr::Field(e) => extract_field_path(e)?,
 
             _ => return err,
         };
 
-        let ident = extract_resource_name_ident(path)?;
+        if resources.contains_key(&path.resource) && path.fields.is_empty()
+            || fields.contains_key(&path)
+        {
+            return Err(parse::Error::new(
+                path.resource.span(),
+                "resource appears more than once in list",
+            ));
+        }
+
+        if path.fields.is_empty() {
+            resources.insert(path.resource, access);
+        } else {
+            fields.insert(path, access);
+        }
+    }
+
+    Ok((resources, fields))
+}
+
+fn extract_field_path(field: ExprField) -> parse::Result<FieldPath> {
+    let mut path = match *field.base {
+        Expr::Path(e) => FieldPath::resource(extract_resource_name_ident(e.path)?),
+        Expr::Field(e) => extract_field_path(e)?,
+        base => {
+            return Err(parse::Error::new(
+                base.span(),
+                "expected a shared resource or a field path like `state.counter`",
+            ));
+        }
+    };
+
+    match field.member {
+        Member::Named(ident) => path.fields.push(ident),
+        Member::Unnamed(index) => {
+            return Err(parse::Error::new(
+                index.span,
+                "only named fields can be accessed separately",
+            ));
+        }
+    }
+
+    Ok(path)
+}
+
+// A list of identifiers like `[A, B]`; `kind` names what they refer to in errors
+pub fn parse_idents(content: ParseStream<'_>, kind: &str) -> parse::Result<Set<Ident>> {
+    let inner;
+    bracketed!(inner in content);
 
-        if resources.contains_key(&ident) {
+    let mut idents = Set::new();
+    for ident in inner.call(Punctuated::<Ident, Token![,]>::parse_terminated)? {
+        if idents.contains(&ident) {
             return Err(parse::Error::new(
                 ident.span(),
-                "resource appears more than once in list",
+                format!("{} appears more than once in list", kind),
             ));
         }
 
-        resources.insert(ident, access);
+        idents.insert(ident);
     }
 
-    Ok(resources)
+    Ok(idents)
 }
 
 fn extract_resource_name_ident(path: Path) -> parse::Result<Ident> {
@@ -282,13 +368,11 @@ pub fn type_is_init_return(ty: &ReturnType, name: &str) -> Result<(Ident, Ident)
                 //
                 // We check the length and the last one here, analysis checks that the user
                 // provided structs are correct.
-                if t.elems.len() == 3 {
-                    if type_is_path(&t.elems[2], &[name, "Monotonics"]) {
-                        return Ok((
-                            extract_init_resource_name_ident(t.elems[0].clone())?,
-                            extract_init_resource_name_ident(t.elems[1].clone())?,
-                        ));
-                    }
+                if t.elems.len() == 3 && type_is_path(&t.elems[2], &[name, "Monotonics"]) {
+                    return Ok((
+                        extract_init_resource_name_ident(t.elems[0].clone())?,
+                        extract_init_resource_name_ident(t.elems[1].clone())?,
+                    ));
                 }
 
                 Err(())
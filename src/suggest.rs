@@ -0,0 +1,54 @@
+//! "Did you mean" suggestions for misspelled names
+
+/// Returns the candidate closest to `name`, if any of them is close enough to be a likely typo
+pub(crate) fn closest<I, S>(name: &str, candidates: I) -> Option<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    // allow roughly one typo every three characters
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    candidates
+        .into_iter()
+        .filter(|candidate| candidate.as_ref() != name)
+        .map(|candidate| (distance(name, candidate.as_ref()), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.as_ref().to_string())
+}
+
+/// Appends a "did you mean" hint to `message` if one of the `candidates` is close to `name`
+pub(crate) fn did_you_mean<I, S>(message: &str, name: &str, candidates: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    match closest(name, candidates) {
+        Some(candidate) => format!("{}; did you mean `{}`?", message, candidate),
+        None => message.to_string(),
+    }
+}
+
+/// Levenshtein distance between `a` and `b`
+fn distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+
+    row[b.len()]
+}
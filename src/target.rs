@@ -0,0 +1,158 @@
+//! Target architectures
+
+use core::ops::RangeInclusive;
+
+/// The architecture the application is compiled for
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub enum Target {
+    /// No architecture specific knowledge; every vector is a plain interrupt
+    #[default]
+    Generic,
+
+    /// ARM Cortex-M
+    CortexM,
+
+    /// RISC-V with a Core-Local Interrupt Controller (CLIC)
+    RiscvClic,
+
+    /// RISC-V with a Platform-Level Interrupt Controller (PLIC)
+    RiscvPlic,
+
+    /// A hosted (`std`) environment where tasks are emulated
+    Host,
+}
+
+impl Target {
+    /// The priority semantics of this architecture
+    pub fn profile(&self) -> Profile {
+        match self {
+            Target::Generic => Profile {
+                priorities: 1..=255,
+                global_ceiling: None,
+                reserved_vectors: &[],
+            },
+
+            Target::CortexM => Profile {
+                priorities: 1..=255,
+                global_ceiling: None,
+                reserved_vectors: &[],
+            },
+
+            Target::RiscvClic => Profile {
+                priorities: 1..=255,
+                global_ceiling: None,
+                reserved_vectors: &[],
+            },
+
+            // the PLIC dispatches every external interrupt through `MachineExternal`
+            Target::RiscvPlic => Profile {
+                priorities: 1..=7,
+                global_ceiling: None,
+                reserved_vectors: &["MachineExternal"],
+            },
+
+            // there's no interrupt controller; every lock is a global critical section
+            Target::Host => Profile {
+                priorities: 1..=255,
+                global_ceiling: Some(0),
+                reserved_vectors: &[],
+            },
+        }
+    }
+
+    /// The core exceptions of this architecture
+    pub fn exceptions(&self) -> &'static [Exception] {
+        match self {
+            Target::CortexM => CORTEX_M_EXCEPTIONS,
+            _ => &[],
+        }
+    }
+
+    /// Looks up the core exception called `name`
+    pub fn exception(&self, name: &str) -> Option<&'static Exception> {
+        self.exceptions().iter().find(|e| e.name == name)
+    }
+}
+
+// Whether `name` is one of the standard Cortex-M core exceptions, whatever the selected target
+pub(crate) fn is_cortex_m_exception(name: &str) -> bool {
+    CORTEX_M_EXCEPTIONS.iter().any(|e| e.name == name)
+}
+
+/// Priority semantics of a target architecture
+///
+/// Priorities in the `#[app]` are always logical: a higher number means higher urgency and `idle`
+/// runs at priority 0. The profile describes how these map onto the hardware.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Profile {
+    /// The priorities tasks can be given
+    pub priorities: RangeInclusive<u8>,
+
+    /// Ceilings above this priority can only be enforced by masking all interrupts
+    ///
+    /// `None` if every ceiling can be enforced by masking interrupts selectively
+    pub global_ceiling: Option<u8>,
+
+    /// Vectors that can't be bound nor used as dispatchers
+    pub reserved_vectors: &'static [&'static str],
+}
+
+impl Profile {
+    /// Whether a resource with this `ceiling` can only be locked by masking all interrupts
+    pub fn needs_global_critical_section(&self, ceiling: u8) -> bool {
+        self.global_ceiling
+            .map(|global| ceiling > global)
+            .unwrap_or(false)
+    }
+
+    /// Whether `vector` is reserved by the architecture
+    pub fn is_reserved(&self, vector: &str) -> bool {
+        self.reserved_vectors.contains(&vector)
+    }
+}
+
+/// A core exception of the target architecture
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct Exception {
+    /// The name of the exception vector
+    pub name: &'static str,
+
+    /// The hardware priority of this exception, if it's fixed
+    pub fixed_priority: Option<i8>,
+
+    /// Whether a task (or monotonic) can be bound to this exception
+    pub bindable: bool,
+}
+
+impl Exception {
+    /// Whether the priority of this exception can be configured
+    pub fn is_configurable(&self) -> bool {
+        self.fixed_priority.is_none()
+    }
+}
+
+const fn exception(name: &'static str, fixed_priority: Option<i8>, bindable: bool) -> Exception {
+    Exception {
+        name,
+        fixed_priority,
+        bindable,
+    }
+}
+
+const CORTEX_M_EXCEPTIONS: &[Exception] = &[
+    exception("Reset", Some(-3), false),
+    exception("NonMaskableInt", Some(-2), true),
+    exception("NMI", Some(-2), true),
+    exception("HardFault", Some(-1), true),
+    exception("MemoryManagement", None, true),
+    exception("BusFault", None, true),
+    exception("UsageFault", None, true),
+    exception("SecureFault", None, true),
+    exception("SVCall", None, true),
+    exception("DebugMonitor", None, true),
+    exception("PendSV", None, true),
+    exception("SysTick", None, true),
+];
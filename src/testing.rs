@@ -0,0 +1,683 @@
+//! Golden-snapshot testing of applications and their analyses
+//!
+//! [`dump`] renders an [`App`] and its [`Analysis`] as stable, human-readable text: maps and sets
+//! are sorted by name so the output doesn't depend on declaration or insertion order.
+//! [`assert_snapshot`] compares the dumps of a directory of applications against checked-in
+//! `.snap` files. [`settings`] is the flag parser shared with the `mock` crate.
+
+use std::{env, fmt::Write as _, fs, path::Path, slice};
+
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::{Attribute, Ident};
+
+use crate::{
+    analyze::{Analysis, Priority, Queue, QueueUser, SendReason},
+    ast::{App, LocalResources, NormalizedType, SharedFields, SharedResources, TaskLocal},
+    target::Target,
+    Map, Set, Settings,
+};
+
+/// Set this environment variable to (re)write the `.snap` files instead of comparing them
+pub const BLESS: &str = "RTIC_SYNTAX_BLESS";
+
+/// Renders `app` and `analysis` as text
+pub fn dump(app: &App, analysis: &Analysis) -> String {
+    let mut s = String::new();
+    dump_app(&mut s, app);
+    s.push('\n');
+    dump_analysis(&mut s, analysis);
+    s
+}
+
+/// Checks every `.rs` application in `dir` against the `.snap` file of the same name in `snapshots`
+///
+/// Each file must contain a module annotated with an `app` attribute (e.g. `#[mock::app(..)]`);
+/// its arguments go through [`settings`]. Applications that fail to parse are snapshotted as their
+/// error messages.
+///
+/// Panics listing every mismatch or missing snapshot, unless the [`BLESS`] environment variable is
+/// set, in which case the snapshots are written instead.
+pub fn assert_snapshot(dir: impl AsRef<Path>, snapshots: impl AsRef<Path>) {
+    let dir = dir.as_ref();
+    let snapshots = snapshots.as_ref();
+    let bless = env::var_os(BLESS).is_some();
+
+    let mut paths = fs::read_dir(dir)
+        .unwrap_or_else(|e| panic!("couldn't read {}: {}", dir.display(), e))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension() == Some("rs".as_ref()))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    let mut failures = vec![];
+    for path in paths {
+        let actual = snapshot(&path);
+        let snap = snapshots
+            .join(path.file_name().unwrap())
+            .with_extension("snap");
+
+        if bless {
+            fs::write(&snap, actual)
+                .unwrap_or_else(|e| panic!("couldn't write {}: {}", snap.display(), e));
+            continue;
+        }
+
+        match fs::read_to_string(&snap) {
+            Ok(expected) if expected == actual => {}
+            Ok(expected) => failures.push(format!(
+                "{}: snapshot mismatch\n--- expected\n{}+++ actual\n{}",
+                snap.display(),
+                expected,
+                actual
+            )),
+            Err(_) => failures.push(format!("{}: missing snapshot", snap.display())),
+        }
+    }
+
+    if !failures.is_empty() {
+        panic!(
+            "{}\nrerun with `{}=1` to update the snapshots",
+            failures.join("\n"),
+            BLESS
+        );
+    }
+}
+
+fn snapshot(path: &Path) -> String {
+    let source = fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("couldn't read {}: {}", path.display(), e));
+    let file = syn::parse_file(&source)
+        .unwrap_or_else(|e| panic!("{}: not valid Rust: {}", path.display(), e));
+    let (args, module) = crate::find_app(&file, &Settings::default())
+        .unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+    let (args, settings) = settings(args);
+
+    match crate::parse2(args, module, settings) {
+        Ok((app, analysis)) => dump(&app, &analysis),
+        Err(e) => e.into_iter().map(|e| format!("error: {}\n", e)).collect(),
+    }
+}
+
+/// Splits the `Settings` flags off the `app` arguments
+///
+/// The bare arguments `parse_binds` and `parse_extern_interrupt` set the corresponding `Settings`
+/// fields; `cortex_m` and `riscv_plic` select the target. `device_vectors = [UART0, ..]` sets the
+/// vectors of the target device.
+pub fn settings(args: TokenStream) -> (TokenStream, Settings) {
+    let mut settings = Settings::default();
+    let mut rest = vec![];
+
+    let mut arg = vec![];
+    let mut tokens = args.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        let end = matches!(&token, TokenTree::Punct(p) if p.as_char() == ',');
+        if !end {
+            arg.push(token);
+        }
+
+        if !end && tokens.peek().is_some() {
+            continue;
+        }
+
+        if let [TokenTree::Ident(ident), TokenTree::Punct(eq), TokenTree::Group(vectors)] =
+            arg.as_slice()
+        {
+            if ident == "device_vectors" && eq.as_char() == '=' {
+                settings.device_vectors = Some(
+                    vectors
+                        .stream()
+                        .into_iter()
+                        .filter_map(|token| match token {
+                            TokenTree::Ident(vector) => Some(vector.to_string()),
+                            _ => None,
+                        })
+                        .collect(),
+                );
+                arg.clear();
+                continue;
+            }
+        }
+
+        let flag = match arg.as_slice() {
+            [TokenTree::Ident(ident)] => Some(ident.to_string()),
+            _ => None,
+        };
+        match flag.as_deref() {
+            Some("parse_binds") => settings.parse_binds = true,
+            Some("parse_extern_interrupt") => settings.parse_extern_interrupt = true,
+            Some("cortex_m") => settings.target = Target::CortexM,
+            Some("riscv_plic") => settings.target = Target::RiscvPlic,
+            _ if arg.is_empty() => {}
+            _ => rest.push(arg.drain(..).collect::<TokenStream>()),
+        }
+        arg.clear();
+    }
+
+    (quote!(#(#rest),*), settings)
+}
+
+fn dump_app(s: &mut String, app: &App) {
+    let args = &app.args;
+    writeln!(s, "app {}", app.name).unwrap();
+    if let Some(device) = &args.device {
+        writeln!(s, "  device: {}", tokens(device)).unwrap();
+    }
+    writeln!(s, "  peripherals: {}", args.peripherals).unwrap();
+    writeln!(s, "  dispatchers: [{}]", names(&args.extern_interrupts)).unwrap();
+    writeln!(s, "  priorities: {:?}", args.priorities).unwrap();
+
+    writeln!(s, "shared resources").unwrap();
+    for (name, res) in sorted(&app.shared_resources) {
+        write!(s, "  {}: {}", name, tokens(&res.ty)).unwrap();
+        if res.properties.lock_free {
+            s.push_str(" #[lock_free]");
+        }
+        cfgs(s, &res.cfgs);
+    }
+
+    writeln!(s, "local resources").unwrap();
+    for (name, res) in sorted(&app.local_resources) {
+        write!(s, "  {}: {}", name, tokens(&res.ty)).unwrap();
+        cfgs(s, &res.cfgs);
+    }
+
+    writeln!(s, "monotonics").unwrap();
+    for (name, monotonic) in sorted(&app.monotonics) {
+        let args = &monotonic.args;
+        write!(
+            s,
+            "  {}: {}, binds = {}, priority = {:?}, default = {}",
+            name,
+            tokens(&monotonic.ty),
+            args.binds,
+            args.priority,
+            args.default
+        )
+        .unwrap();
+        if let Some(tick_hz) = args.tick_hz {
+            write!(s, ", tick_hz = {}", tick_hz).unwrap();
+        }
+        if let Some(bits) = args.bits {
+            write!(s, ", bits = {}", bits).unwrap();
+        }
+        cfgs(s, &monotonic.cfgs);
+    }
+
+    writeln!(s, "message channels").unwrap();
+    for (name, channel) in sorted(&app.message_channels) {
+        write!(
+            s,
+            "  {}: {}, capacity = {}",
+            name,
+            tokens(&channel.ty),
+            channel.args.capacity
+        )
+        .unwrap();
+        cfgs(s, &channel.cfgs);
+    }
+
+    writeln!(s, "init {}", app.init.name).unwrap();
+    resources(s, None, &app.init.args.local_resources);
+    lists(s, &[("spawn", &app.init.args.spawn)]);
+
+    if let Some(idle) = &app.idle {
+        writeln!(s, "idle {}", idle.name).unwrap();
+        resources(
+            s,
+            Some((&idle.args.shared_resources, &idle.args.shared_fields)),
+            &idle.args.local_resources,
+        );
+        lists(s, &[("spawn", &idle.args.spawn)]);
+    }
+
+    for (name, task) in sorted(&app.hardware_tasks) {
+        let args = &task.args;
+        write!(
+            s,
+            "hardware task {}: binds = {}, priority = {}",
+            name, args.binds, args.priority
+        )
+        .unwrap();
+        if let Some(deadline) = &args.deadline {
+            write!(s, ", deadline = {}", tokens(deadline)).unwrap();
+        }
+        if task.is_extern {
+            s.push_str(", extern");
+        }
+        cfgs(s, &task.cfgs);
+        resources(
+            s,
+            Some((&args.shared_resources, &args.shared_fields)),
+            &args.local_resources,
+        );
+        lists(
+            s,
+            &[
+                ("send", &args.send),
+                ("recv", &args.recv),
+                ("spawn", &args.spawn),
+            ],
+        );
+    }
+
+    for (name, task) in sorted(&app.software_tasks) {
+        let args = &task.args;
+        let inputs = task
+            .inputs
+            .iter()
+            .map(|input| tokens(&input.ty))
+            .collect::<Vec<_>>();
+        write!(
+            s,
+            "software task {}({}): priority = {}, capacity = {}",
+            name,
+            inputs.join(", "),
+            args.priority,
+            args.capacity
+        )
+        .unwrap();
+        if let Some(monotonic) = &args.monotonic {
+            write!(s, ", monotonic = {}", monotonic).unwrap();
+        }
+        if let Some(period) = &args.period {
+            write!(s, ", period = {}", tokens(period)).unwrap();
+        }
+        if let Some(offset) = &args.offset {
+            write!(s, ", offset = {}", tokens(offset)).unwrap();
+        }
+        if let Some(deadline) = &args.deadline {
+            write!(s, ", deadline = {}", tokens(deadline)).unwrap();
+        }
+        if task.is_extern {
+            s.push_str(", extern");
+        }
+        cfgs(s, &task.cfgs);
+        resources(
+            s,
+            Some((&args.shared_resources, &args.shared_fields)),
+            &args.local_resources,
+        );
+        lists(
+            s,
+            &[
+                ("send", &args.send),
+                ("recv", &args.recv),
+                ("spawn", &args.spawn),
+            ],
+        );
+    }
+}
+
+fn dump_analysis(s: &mut String, analysis: &Analysis) {
+    writeln!(s, "tasks").unwrap();
+    for task in &analysis.tasks {
+        write!(
+            s,
+            "  {}: {:?}, priority = {}",
+            task.name, task.kind, task.priority
+        )
+        .unwrap();
+        if let Some(binds) = &task.binds {
+            write!(s, ", binds = {}", binds).unwrap();
+        }
+        if let Some(channel) = task.channel {
+            write!(s, ", channel = {}", channel).unwrap();
+        }
+        if let Some(dispatcher) = &task.dispatcher {
+            write!(s, ", dispatcher = {}", dispatcher).unwrap();
+        }
+        if !task.message.is_empty() {
+            let message = task.message.iter().map(tokens).collect::<Vec<_>>();
+            write!(s, ", message = ({})", message.join(", ")).unwrap();
+        }
+        s.push('\n');
+    }
+
+    writeln!(s, "channels").unwrap();
+    for (priority, channel) in &analysis.channels {
+        let tasks = channel
+            .tasks
+            .iter()
+            .map(|task| task.to_string())
+            .collect::<Vec<_>>();
+        writeln!(
+            s,
+            "  {}: capacity = {}, tasks = [{}]",
+            priority,
+            channel.capacity,
+            tasks.join(", ")
+        )
+        .unwrap();
+    }
+
+    writeln!(s, "message channels").unwrap();
+    for (name, channel) in sorted(&analysis.message_channels) {
+        let senders = channel
+            .senders
+            .iter()
+            .map(|(task, priority)| format!("{} ({})", task, priority))
+            .collect::<Vec<_>>();
+        let (receiver, priority) = &channel.receiver;
+        writeln!(
+            s,
+            "  {}: capacity = {}, senders = [{}], receiver = {} ({}), {:?}",
+            name,
+            channel.capacity,
+            senders.join(", "),
+            receiver,
+            priority,
+            channel.ownership
+        )
+        .unwrap();
+    }
+
+    writeln!(s, "timer queues").unwrap();
+    for (monotonic, tq) in sorted(&analysis.timer_queues) {
+        let tasks = tq
+            .tasks
+            .iter()
+            .map(|task| task.to_string())
+            .collect::<Vec<_>>();
+        writeln!(
+            s,
+            "  {}: priority = {}, capacity = {}, tasks = [{}]",
+            monotonic,
+            tq.priority,
+            tq.capacity,
+            tasks.join(", ")
+        )
+        .unwrap();
+    }
+
+    writeln!(s, "periodic tasks").unwrap();
+    for (name, task) in sorted(&analysis.periodic_tasks) {
+        write!(
+            s,
+            "  {}: monotonic = {}, period = {}",
+            name,
+            task.monotonic,
+            tokens(&task.period)
+        )
+        .unwrap();
+        if let Some(ticks) = task.period_ticks {
+            write!(s, " ({} ticks)", ticks).unwrap();
+        }
+        if let Some(offset) = &task.offset {
+            write!(s, ", offset = {}", tokens(offset)).unwrap();
+        }
+        if let Some(ticks) = task.offset_ticks {
+            write!(s, " ({} ticks)", ticks).unwrap();
+        }
+        s.push('\n');
+    }
+
+    writeln!(s, "queues").unwrap();
+    for (queue, resource) in &analysis.queues {
+        let users = resource
+            .users
+            .iter()
+            .map(|(user, priority)| match user {
+                QueueUser::Spawner(name) => format!("{} ({})", name, priority),
+                QueueUser::Dispatcher => format!("dispatcher ({})", priority),
+                QueueUser::Monotonic(name) => format!("monotonic {} ({})", name, priority),
+            })
+            .collect::<Vec<_>>();
+        let queue = match queue {
+            Queue::Ready(priority) => format!("ready {}", priority),
+            Queue::Timer(name) => format!("timer {}", name),
+        };
+        writeln!(
+            s,
+            "  {}: {:?}, users = [{}]",
+            queue,
+            resource.ownership,
+            users.join(", ")
+        )
+        .unwrap();
+    }
+
+    writeln!(s, "ownerships").unwrap();
+    for (name, ownership) in sorted(&analysis.ownerships) {
+        writeln!(s, "  {}: {:?}", name, ownership).unwrap();
+    }
+
+    if !analysis.field_ownerships.is_empty() {
+        let mut field_ownerships = analysis
+            .field_ownerships
+            .iter()
+            .map(|(path, ownership)| format!("  {}: {:?}", path, ownership))
+            .collect::<Vec<_>>();
+        field_ownerships.sort();
+        writeln!(s, "field ownerships").unwrap();
+        for line in field_ownerships {
+            writeln!(s, "{}", line).unwrap();
+        }
+    }
+
+    writeln!(s, "locks").unwrap();
+    for (context, locks) in sorted(&analysis.locks) {
+        for (name, lock) in sorted(locks) {
+            writeln!(
+                s,
+                "  {}.{}: {:?}, priority = {}, ceiling = {}, needs_lock = {}, critical_section = {}, lock_free = {}, read_only = {}",
+                context,
+                name,
+                lock.access,
+                lock.priority,
+                lock.ceiling,
+                lock.needs_lock,
+                lock.critical_section,
+                lock.lock_free,
+                lock.read_only
+            )
+            .unwrap();
+        }
+    }
+
+    if analysis.field_locks.values().any(|locks| !locks.is_empty()) {
+        writeln!(s, "field locks").unwrap();
+        for (context, locks) in sorted(&analysis.field_locks) {
+            let mut locks = locks.iter().collect::<Vec<_>>();
+            locks.sort_by_cached_key(|(path, _)| path.to_string());
+            for (path, lock) in locks {
+                writeln!(
+                    s,
+                    "  {}.{}: {:?}, priority = {}, ceiling = {}, needs_lock = {}, critical_section = {}, lock_free = {}, read_only = {}",
+                    context,
+                    path,
+                    lock.access,
+                    lock.priority,
+                    lock.ceiling,
+                    lock.needs_lock,
+                    lock.critical_section,
+                    lock.lock_free,
+                    lock.read_only
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    let mut critical_sections = analysis
+        .critical_sections
+        .iter()
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+    critical_sections.sort();
+    writeln!(s, "critical sections: [{}]", critical_sections.join(", ")).unwrap();
+
+    let assigned_priorities = sorted(&analysis.assigned_priorities)
+        .into_iter()
+        .map(|(task, priority)| format!("{} = {}", task, priority))
+        .collect::<Vec<_>>();
+    writeln!(
+        s,
+        "assigned priorities: [{}]",
+        assigned_priorities.join(", ")
+    )
+    .unwrap();
+
+    writeln!(
+        s,
+        "used shared resources: [{}]",
+        names(&analysis.shared_resource_locations)
+    )
+    .unwrap();
+    writeln!(
+        s,
+        "used local resources: [{}]",
+        names(&analysis.local_resource_locations)
+    )
+    .unwrap();
+
+    let mut send_types = analysis
+        .send_types
+        .iter()
+        .map(|(send_type, reasons)| {
+            let reasons = reasons.iter().map(send_reason).collect::<Vec<_>>();
+            format!("  {}: {}", ty(send_type), reasons.join("; "))
+        })
+        .collect::<Vec<_>>();
+    send_types.sort();
+    writeln!(s, "send types").unwrap();
+    for line in send_types {
+        writeln!(s, "{}", line).unwrap();
+    }
+
+    let mut sync_types = analysis.sync_types.iter().map(ty).collect::<Vec<_>>();
+    sync_types.sort();
+    writeln!(s, "sync types: [{}]", sync_types.join(", ")).unwrap();
+}
+
+fn resources(
+    s: &mut String,
+    shared: Option<(&SharedResources, &SharedFields)>,
+    local: &LocalResources,
+) {
+    if let Some((shared, fields)) = shared {
+        let mut shared = shared
+            .iter()
+            .map(|(name, access)| (name.to_string(), *access))
+            .chain(
+                fields
+                    .iter()
+                    .map(|(path, access)| (path.to_string(), *access)),
+            )
+            .collect::<Vec<_>>();
+        shared.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let shared = shared
+            .into_iter()
+            .map(|(name, access)| {
+                if access.is_shared() {
+                    format!("&{}", name)
+                } else {
+                    name
+                }
+            })
+            .collect::<Vec<_>>();
+        writeln!(s, "  shared: [{}]", shared.join(", ")).unwrap();
+    }
+
+    let local = sorted(local)
+        .into_iter()
+        .map(|(name, local)| match local {
+            TaskLocal::External => name.to_string(),
+            TaskLocal::Declared(local) => {
+                format!("{}: {} = {}", name, tokens(&local.ty), tokens(&local.expr))
+            }
+        })
+        .collect::<Vec<_>>();
+    writeln!(s, "  local: [{}]", local.join(", ")).unwrap();
+}
+
+// The non-empty identifier lists among `args`, like `send = [..]`
+fn lists(s: &mut String, args: &[(&str, &Set<Ident>)]) {
+    for (arg, idents) in args {
+        if !idents.is_empty() {
+            let mut idents = idents
+                .iter()
+                .map(|ident| ident.to_string())
+                .collect::<Vec<_>>();
+            idents.sort();
+            writeln!(s, "  {}: [{}]", arg, idents.join(", ")).unwrap();
+        }
+    }
+}
+
+fn cfgs(s: &mut String, cfgs: &[Attribute]) {
+    for cfg in cfgs {
+        write!(s, " {}", tokens(cfg)).unwrap();
+    }
+    s.push('\n');
+}
+
+fn sorted<T>(map: &Map<T>) -> Vec<(&Ident, &T)> {
+    let mut entries = map.iter().collect::<Vec<_>>();
+    entries.sort_by_cached_key(|(name, _)| name.to_string());
+    entries
+}
+
+fn names<T>(map: &Map<T>) -> String {
+    sorted(map)
+        .into_iter()
+        .map(|(name, _)| name.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn send_reason(reason: &SendReason) -> String {
+    let contexts = |contexts: &[(Ident, Priority)]| {
+        contexts
+            .iter()
+            .map(|(name, priority)| format!("{} ({})", name, priority))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    match reason {
+        SendReason::SharedResource { resource, users } => {
+            format!("shared resource `{}` used by {}", resource, contexts(users))
+        }
+        SendReason::LocalResource { resource, users } => {
+            format!("local resource `{}` used by {}", resource, contexts(users))
+        }
+        SendReason::MessageChannel {
+            channel,
+            senders,
+            receiver,
+        } => format!(
+            "channel `{}` from {} to {}",
+            channel,
+            contexts(senders),
+            contexts(slice::from_ref(receiver))
+        ),
+        SendReason::TaskInput {
+            task,
+            priority,
+            spawners,
+        } => format!(
+            "input of `{}` ({}) spawned from {}",
+            task,
+            priority,
+            spawners
+                .as_ref()
+                .map_or_else(|| "anywhere".to_string(), |spawners| contexts(spawners))
+        ),
+    }
+}
+
+// The type as written, followed by its canonical form if that's different
+fn ty(ty: &NormalizedType) -> String {
+    let original = tokens(&ty.original);
+    let normalized = tokens(&ty.normalized);
+    if original == normalized {
+        original
+    } else {
+        format!("{} (= {})", original, normalized)
+    }
+}
+
+fn tokens(tokens: impl ToTokens) -> String {
+    tokens.into_token_stream().to_string()
+}
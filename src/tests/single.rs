@@ -1,5 +1,13 @@
-use crate::{analyze::Ownership, Settings};
+use crate::{
+    analyze::{Ownership, Queue, QueueUser, SendReason},
+    ast::{Access, FieldPath, TaskKind},
+    cfg::CfgSet,
+    target::Target,
+    Context, Settings,
+};
+use proc_macro2::Span;
 use quote::quote;
+use syn::Ident;
 
 #[test]
 fn unused_task() {
@@ -180,7 +188,7 @@ fn send_spawn() {
     )
     .unwrap();
 
-    let ty = analysis.send_types.iter().next().unwrap();
+    let ty = analysis.send_types.keys().next().unwrap();
     assert_eq!(quote!(#ty).to_string(), "X");
 }
 
@@ -210,7 +218,7 @@ fn send_shared_resource() {
     )
     .unwrap();
 
-    let ty = analysis.send_types.iter().next().unwrap();
+    let ty = analysis.send_types.keys().next().unwrap();
     assert_eq!(quote!(#ty).to_string(), "X");
 }
 
@@ -240,7 +248,7 @@ fn send_local_resource() {
     )
     .unwrap();
 
-    let ty = analysis.send_types.iter().next().unwrap();
+    let ty = analysis.send_types.keys().next().unwrap();
     assert_eq!(quote!(#ty).to_string(), "X");
 }
 
@@ -270,7 +278,7 @@ fn send_shared_with_init() {
     )
     .unwrap();
 
-    let ty = analysis.send_types.iter().next().unwrap();
+    let ty = analysis.send_types.keys().next().unwrap();
     assert_eq!(quote!(#ty).to_string(), "i32");
 }
 
@@ -432,3 +440,1099 @@ fn late_resources() {
     let late = &app.shared_resources;
     assert_eq!(late.len(), 1);
 }
+
+#[test]
+fn unknown_vector() {
+    // typos in `binds` are caught when the device vectors are known
+    let settings = Settings {
+        parse_binds: true,
+        device_vectors: Some(vec!["UART0".to_string(), "UART1".to_string()]),
+        ..Settings::default()
+    };
+
+    let err = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[shared]
+                struct Shared {}
+
+                #[local]
+                struct Local {}
+
+                #[init]
+                fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+                #[task(binds = UARTO)]
+                fn foo(_: foo::Context) {}
+            }
+        ),
+        settings,
+    )
+    .err()
+    .unwrap();
+
+    assert_eq!(
+        err.to_string(),
+        "this interrupt is not a vector of the target device; did you mean `UART0`?"
+    );
+}
+
+#[test]
+fn core_exceptions_are_not_device_vectors() {
+    // SVD files don't list the core exceptions; they are accepted with the default target too
+    let settings = Settings {
+        parse_binds: true,
+        device_vectors: Some(vec!["UART0".to_string()]),
+        ..Settings::default()
+    };
+
+    let (app, _) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[shared]
+                struct Shared {}
+
+                #[local]
+                struct Local {}
+
+                #[init]
+                fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+                #[monotonic(binds = SysTick)]
+                type Mono = hal::Mono;
+
+                #[task(binds = PendSV)]
+                fn foo(_: foo::Context) {}
+
+                #[task(binds = UART0)]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        settings,
+    )
+    .unwrap();
+
+    assert_eq!(app.hardware_tasks.len(), 2);
+    assert_eq!(app.monotonics.len(), 1);
+}
+
+#[test]
+fn unknown_dispatcher() {
+    // dispatchers are checked against the device vectors as well
+    let settings = Settings {
+        device_vectors: Some(vec!["UART0".to_string(), "SSI0".to_string()]),
+        ..Settings::default()
+    };
+
+    let err = crate::parse2(
+        quote!(dispatchers = [SSI0, GPIOA]),
+        quote!(
+            mod app {
+                #[shared]
+                struct Shared {}
+
+                #[local]
+                struct Local {}
+
+                #[init]
+                fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+            }
+        ),
+        settings,
+    )
+    .err()
+    .unwrap();
+
+    assert_eq!(
+        err.to_string(),
+        "this interrupt is not a vector of the target device"
+    );
+}
+
+#[test]
+fn critical_section_host() {
+    // on a host there's no interrupt controller, contended resources need a critical section
+    let (_app, analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[shared]
+                struct Shared {
+                    x: i32,
+                    y: i32,
+                }
+
+                #[local]
+                struct Local {}
+
+                #[init]
+                fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+                #[task(shared = [x, y])]
+                fn foo(_: foo::Context) {}
+
+                #[task(priority = 2, shared = [x])]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        Settings {
+            target: Target::Host,
+            ..Settings::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(analysis.critical_sections.len(), 1);
+    assert_eq!(analysis.critical_sections[0].to_string(), "x");
+}
+
+#[test]
+fn parse_recovering() {
+    // broken items are skipped, the rest of the app is still analyzed
+    let (app, analysis, errors) = crate::parse_recovering(
+        quote!(),
+        quote!(
+            mod app {
+                #[shared]
+                struct Shared {
+                    x: i32,
+                }
+
+                #[local]
+                struct Local {}
+
+                #[init]
+                fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+                #[task(shared = [x])]
+                fn foo(_: foo::Context) {}
+
+                #[task(priority = 2, shared = [x, y])]
+                fn bar(_: bar::Context) {}
+
+                #[task(shared = [x y])]
+                fn baz(_: baz::Context) {}
+
+                #[task]
+                fn qux(_: qux::Context) -> u32 {}
+            }
+        ),
+        Settings::default(),
+    );
+
+    let app = app.unwrap();
+    let analysis = analysis.unwrap();
+
+    let tasks = app
+        .software_tasks
+        .keys()
+        .map(|t| t.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(tasks, ["foo", "bar"]);
+    assert_eq!(errors.len(), 3);
+    assert!(errors[2]
+        .to_string()
+        .starts_with("this shared resource has NOT been declared"));
+
+    let (res, ownership) = analysis.ownerships.iter().next().unwrap();
+    assert_eq!(res.to_string(), "x");
+    assert_eq!(*ownership, Ownership::Contended { ceiling: 2 });
+}
+
+#[test]
+fn parse_recovering_broken_init() {
+    // a broken `#[init]` is replaced by a placeholder instead of discarding the whole app
+    let (app, analysis, errors) = crate::parse_recovering(
+        quote!(),
+        quote!(
+            mod app {
+                #[shared]
+                struct Shared {
+                    x: i32,
+                }
+
+                #[local]
+                struct Local {}
+
+                #[init]
+                fn init(_: init::Context) {}
+
+                #[task(shared = [x])]
+                fn foo(_: foo::Context) {}
+            }
+        ),
+        Settings::default(),
+    );
+
+    let app = app.unwrap();
+    assert!(analysis.is_some());
+    assert_eq!(app.init.name.to_string(), "init");
+    assert_eq!(app.init.user_shared_struct.to_string(), "Shared");
+    assert_eq!(app.software_tasks.len(), 1);
+
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0]
+        .to_string()
+        .starts_with("the `#[init]` function must have signature"));
+}
+
+#[test]
+fn contexts() {
+    let (app, _analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[shared]
+                struct Shared {
+                    x: i32,
+                }
+
+                #[local]
+                struct Local {
+                    y: i32,
+                }
+
+                #[init]
+                fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+                #[idle(shared = [x])]
+                fn idle(_: idle::Context) -> ! {
+                    loop {}
+                }
+
+                #[task(priority = 2, shared = [x], local = [y])]
+                fn foo(_: foo::Context) {}
+
+                #[task(binds = UART0, priority = 3)]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        Settings {
+            parse_binds: true,
+            ..Settings::default()
+        },
+    )
+    .unwrap();
+
+    let contexts = app
+        .contexts()
+        .map(|c| (c.name().to_string(), c.kind(), c.priority()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        contexts,
+        [
+            ("init".to_string(), TaskKind::Init, 0),
+            ("idle".to_string(), TaskKind::Idle, 0),
+            ("bar".to_string(), TaskKind::HardwareTask, 3),
+            ("foo".to_string(), TaskKind::SoftwareTask, 2),
+        ]
+    );
+
+    let x = app.shared_resources.keys().next().unwrap();
+    let users = app
+        .tasks_accessing(x)
+        .map(|c| c.name().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(users, ["idle", "foo"]);
+
+    let foo = app.software_tasks.keys().next().unwrap();
+    let resources = app
+        .resources_of(foo)
+        .unwrap()
+        .into_iter()
+        .map(|r| r.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(resources, ["x", "y"]);
+    assert!(app.context(foo).unwrap().kind() == TaskKind::SoftwareTask);
+}
+
+#[test]
+fn context_api() {
+    let (app, _analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[shared]
+                struct Shared {
+                    x: i32,
+                }
+
+                #[local]
+                struct Local {}
+
+                #[init]
+                fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+                #[monotonic(binds = Tim1)]
+                type Fast = hal::Tim1Monotonic;
+
+                #[monotonic(binds = Tim2, priority = 5)]
+                type Slow = hal::Tim2Monotonic;
+
+                #[task(shared = [x])]
+                fn foo(_: foo::Context) {}
+
+                #[cfg(feature = "bar")]
+                #[task(priority = 3, shared = [x])]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    let contexts = Context::all(&app)
+        .into_iter()
+        .map(|c| {
+            (
+                c.ident(&app).to_string(),
+                c.priority(&app),
+                c.shared_resources(&app).count(),
+                c.cfgs(&app).len(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        contexts,
+        [
+            ("init".to_string(), 0, 0, 0),
+            ("foo".to_string(), 1, 1, 0),
+            ("bar".to_string(), 3, 1, 1),
+            ("Fast".to_string(), 3, 0, 0),
+            ("Slow".to_string(), 5, 0, 0),
+        ]
+    );
+}
+
+#[test]
+fn locks() {
+    let (_app, analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[shared]
+                struct Shared {
+                    a: u32,
+                    #[lock_free]
+                    b: u32,
+                    c: u32,
+                }
+
+                #[local]
+                struct Local {}
+
+                #[init]
+                fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+                #[idle(shared = [a])]
+                fn idle(_: idle::Context) -> ! {}
+
+                #[task(priority = 2, shared = [a, b, &c])]
+                fn foo(_: foo::Context) {}
+
+                #[task(priority = 2, shared = [b, &c])]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    let tasks = analysis
+        .locks
+        .keys()
+        .map(|task| task.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(tasks, ["init", "idle", "foo", "bar"]);
+    let ident = |name| Ident::new(name, Span::call_site());
+    let lock = |task, resource| analysis.locks[&ident(task)][&ident(resource)];
+    assert!(analysis.locks[&ident("init")].is_empty());
+
+    let a = lock("idle", "a");
+    assert_eq!((a.priority, a.ceiling, a.needs_lock), (0, 2, true));
+    assert!(!a.lock_free && !a.read_only && a.access == Access::Exclusive);
+
+    let a = lock("foo", "a");
+    assert_eq!((a.priority, a.ceiling, a.needs_lock), (2, 2, false));
+
+    let b = lock("bar", "b");
+    assert!(b.lock_free && !b.needs_lock);
+
+    let c = lock("foo", "c");
+    assert!(c.read_only && c.access == Access::Shared && !c.needs_lock);
+}
+
+#[test]
+fn tasks() {
+    let (_app, analysis) = crate::parse2(
+        quote!(dispatchers = [SSI0]),
+        quote!(
+            mod app {
+                #[shared]
+                struct Shared {}
+
+                #[local]
+                struct Local {}
+
+                #[init]
+                fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+                #[task(priority = 3)]
+                fn low(_: low::Context) {}
+
+                #[task(binds = UART0, priority = 2)]
+                fn uart0(_: uart0::Context) {}
+
+                #[task(priority = 5)]
+                fn high(_: high::Context) {}
+            }
+        ),
+        Settings {
+            parse_binds: true,
+            ..Settings::default()
+        },
+    )
+    .unwrap();
+
+    let tasks = analysis
+        .tasks
+        .iter()
+        .map(|task| {
+            (
+                task.name.to_string(),
+                task.kind,
+                task.priority,
+                task.binds.as_ref().map(|binds| binds.to_string()),
+                task.channel,
+                task.dispatcher
+                    .as_ref()
+                    .map(|dispatcher| dispatcher.to_string()),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        tasks,
+        [
+            ("init".to_string(), TaskKind::Init, 0, None, None, None),
+            (
+                "uart0".to_string(),
+                TaskKind::HardwareTask,
+                2,
+                Some("UART0".to_string()),
+                None,
+                None
+            ),
+            (
+                "low".to_string(),
+                TaskKind::SoftwareTask,
+                3,
+                None,
+                Some(3),
+                None
+            ),
+            (
+                "high".to_string(),
+                TaskKind::SoftwareTask,
+                5,
+                None,
+                Some(5),
+                Some("SSI0".to_string())
+            ),
+        ]
+    );
+}
+
+#[test]
+fn parse_str() {
+    let source = r#"
+        use rtic::app;
+
+        #[rtic::app(device = pac, dispatchers = [SSI0])]
+        mod app {
+            #[shared]
+            struct Shared {}
+
+            #[local]
+            struct Local {}
+
+            #[init]
+            fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+            #[task]
+            fn foo(_: foo::Context) {}
+        }
+    "#;
+
+    let (app, analysis) = crate::parse_str(source, Settings::default()).unwrap();
+    assert_eq!(app.args.extern_interrupts.len(), 1);
+    assert_eq!(analysis.tasks.len(), 2);
+
+    let settings = Settings {
+        app_attribute: Some(syn::parse_quote!(rtic::app)),
+        ..Settings::default()
+    };
+    assert!(crate::parse_str(source, settings).is_ok());
+
+    let settings = Settings {
+        app_attribute: Some(syn::parse_quote!(mock::app)),
+        ..Settings::default()
+    };
+    let error = crate::parse_str(source, settings).err().unwrap();
+    assert_eq!(error.to_string(), "no `#[mock::app]` module found");
+
+    let twice = format!("{}\n{}", source, source.replace("mod app", "mod app2"));
+    let error = crate::parse_str(&twice, Settings::default()).err().unwrap();
+    assert_eq!(error.to_string(), "only one `#[app]` module can be defined");
+}
+
+#[test]
+fn cfgs() {
+    let app = quote!(
+        mod app {
+            #[shared]
+            struct Shared {
+                x: u32,
+                #[cfg(feature = "b")]
+                y: u32,
+            }
+
+            #[local]
+            struct Local {}
+
+            #[init]
+            fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+            #[task(shared = [x])]
+            fn foo(_: foo::Context) {}
+
+            #[cfg(all(feature = "b", not(debug_assertions)))]
+            #[task(priority = 2, shared = [x, y])]
+            fn bar(_: bar::Context) {}
+
+            #[cfg(any(feature = "a", feature = "b"))]
+            #[task(priority = 3)]
+            fn baz(_: baz::Context) {}
+        }
+    );
+
+    // without a configuration every task is analyzed
+    let (_app, analysis) = crate::parse2(quote!(), app.clone(), Settings::default()).unwrap();
+    assert_eq!(analysis.tasks.len(), 4);
+    assert_eq!(analysis.ownerships[0], Ownership::Contended { ceiling: 2 });
+
+    let mut cfgs = CfgSet::new();
+    cfgs.enable("debug_assertions").enable_value("feature", "a");
+    let settings = Settings {
+        cfgs: Some(cfgs),
+        ..Settings::default()
+    };
+    let (_app, analysis) = crate::parse2(quote!(), app, settings).unwrap();
+    let tasks = analysis
+        .tasks
+        .iter()
+        .map(|task| task.name.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(tasks, ["init", "foo", "baz"]);
+    assert_eq!(analysis.ownerships.len(), 1);
+    assert_eq!(analysis.ownerships[0], Ownership::Owned { priority: 1 });
+    assert_eq!(analysis.shared_resource_locations.len(), 1);
+    assert_eq!(analysis.channels.len(), 2);
+
+    let settings = Settings {
+        cfgs: Some(CfgSet::new()),
+        ..Settings::default()
+    };
+    let error = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[shared]
+                struct Shared {}
+
+                #[local]
+                struct Local {}
+
+                #[init]
+                fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+                #[cfg(feature = 1)]
+                #[task]
+                fn foo(_: foo::Context) {}
+            }
+        ),
+        settings,
+    )
+    .err()
+    .unwrap();
+    assert_eq!(error.to_string(), "this `cfg` predicate can't be evaluated");
+}
+
+#[test]
+fn timer_queues() {
+    let (app, analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[shared]
+                struct Shared {}
+
+                #[local]
+                struct Local {}
+
+                #[init]
+                fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+                #[monotonic(binds = Tim1, default = true)]
+                type Fast = hal::Tim1Monotonic;
+
+                #[monotonic(binds = Tim2)]
+                type Slow = hal::Tim2Monotonic;
+
+                #[monotonic(binds = Tim3, priority = 7)]
+                type Other = hal::Tim3Monotonic;
+
+                #[task(capacity = 2)]
+                fn foo(_: foo::Context) {}
+
+                #[task(priority = 4, capacity = 3, monotonic = Fast)]
+                fn bar(_: bar::Context) {}
+
+                #[task(priority = 2, monotonic = Slow)]
+                fn baz(_: baz::Context) {}
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    assert_eq!(app.default_monotonic().unwrap().ident.to_string(), "Fast");
+
+    let timer_queues = analysis
+        .timer_queues
+        .iter()
+        .map(|(name, tq)| {
+            (
+                name.to_string(),
+                tq.priority,
+                tq.capacity,
+                tq.tasks.iter().map(|t| t.to_string()).collect::<Vec<_>>(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        timer_queues,
+        [
+            (
+                "Fast".to_string(),
+                4,
+                5,
+                vec!["bar".to_string(), "foo".to_string()]
+            ),
+            (
+                "Slow".to_string(),
+                2,
+                3,
+                vec!["baz".to_string(), "foo".to_string()]
+            ),
+            ("Other".to_string(), 7, 2, vec!["foo".to_string()]),
+        ]
+    );
+
+    // the handler contexts agree with the analysis
+    for context in Context::all(&app) {
+        if let Context::Monotonic(name) = context {
+            assert_eq!(context.priority(&app), analysis.timer_queues[name].priority);
+        }
+    }
+}
+
+#[test]
+fn queues() {
+    let (_app, analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[shared]
+                struct Shared {}
+
+                #[local]
+                struct Local {}
+
+                #[init]
+                fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+                #[idle]
+                fn idle(_: idle::Context) -> ! {}
+
+                #[monotonic(binds = Tim1, priority = 3)]
+                type Mono = hal::Tim1Monotonic;
+
+                #[task(priority = 2)]
+                fn foo(_: foo::Context) {}
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    let ready = &analysis.queues[&Queue::Ready(2)];
+    assert_eq!(
+        ready.users,
+        [
+            (QueueUser::Spawner(ident("idle")), 0),
+            (QueueUser::Spawner(ident("foo")), 2),
+            (QueueUser::Monotonic(ident("Mono")), 3),
+            (QueueUser::Dispatcher, 2),
+        ]
+    );
+    assert_eq!(ready.ownership, Ownership::Contended { ceiling: 3 });
+    assert!(ready.ownership.needs_lock(2));
+
+    let timer = &analysis.queues[&Queue::Timer(ident("Mono"))];
+    assert_eq!(timer.users.len(), 3);
+    assert_eq!(timer.ownership, Ownership::Contended { ceiling: 3 });
+    assert!(!timer.ownership.needs_lock(3));
+}
+
+#[test]
+fn message_channels() {
+    let (app, analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[shared]
+                struct Shared {}
+
+                #[local]
+                struct Local {}
+
+                #[init]
+                fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+                #[channel(capacity = 4)]
+                type Samples = u16;
+
+                #[channel(capacity = 1)]
+                type Unused = u8;
+
+                #[task(binds = Adc, priority = 3, send = [Samples])]
+                fn adc(_: adc::Context) {}
+
+                #[task(priority = 1, recv = [Samples])]
+                fn filter(_: filter::Context) {}
+            }
+        ),
+        Settings {
+            parse_binds: true,
+            ..Settings::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(analysis.message_channels.len(), 1);
+    let samples = &analysis.message_channels[&ident("Samples")];
+    assert_eq!(samples.capacity, 4);
+    assert_eq!(samples.senders, [(ident("adc"), 3)]);
+    assert_eq!(samples.receiver, (ident("filter"), 1));
+    assert_eq!(samples.ownership, Ownership::Contended { ceiling: 3 });
+    assert!(samples.ownership.needs_lock(1));
+    assert!(!samples.ownership.needs_lock(3));
+    assert!(analysis
+        .send_types
+        .contains_key(&app.normalize_type(&samples.ty)));
+}
+
+#[test]
+fn background_tasks() {
+    let (app, analysis) = crate::parse2(
+        quote!(dispatchers = [UART0]),
+        quote!(
+            mod app {
+                #[shared]
+                struct Shared {
+                    log: Log,
+                }
+
+                #[local]
+                struct Local {}
+
+                #[init]
+                fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+                #[idle(shared = [log])]
+                fn idle(cx: idle::Context) -> ! {
+                    loop {
+                        cx.yield_now();
+                    }
+                }
+
+                #[task(priority = 0, shared = [log])]
+                fn flush(_: flush::Context) {}
+
+                #[task(priority = 5)]
+                fn urgent(_: urgent::Context) {}
+            }
+        ),
+        Settings {
+            optimize_priorities: true,
+            ..Settings::default()
+        },
+    )
+    .unwrap();
+
+    assert!(app.idle.as_ref().unwrap().yields());
+    assert_eq!(app.software_tasks[&ident("flush")].args.priority, 0);
+    assert_eq!(app.software_tasks[&ident("urgent")].args.priority, 1);
+
+    assert_eq!(analysis.channels.keys().collect::<Vec<_>>(), [&0, &1]);
+    let flush = analysis
+        .tasks
+        .iter()
+        .find(|task| task.name == "flush")
+        .unwrap();
+    assert_eq!(flush.channel, Some(0));
+    assert_eq!(flush.dispatcher, None);
+    let urgent = analysis
+        .tasks
+        .iter()
+        .find(|task| task.name == "urgent")
+        .unwrap();
+    assert_eq!(urgent.dispatcher, Some(ident("UART0")));
+
+    assert_eq!(
+        analysis.ownerships[&ident("log")],
+        Ownership::CoOwned { priority: 0 }
+    );
+    assert!(analysis.send_types.is_empty());
+}
+
+#[test]
+fn field_locks() {
+    let (app, analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[shared]
+                struct Shared {
+                    state: State,
+                }
+
+                #[local]
+                struct Local {}
+
+                #[init]
+                fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+                #[task(priority = 1, shared = [state.counter, &state.config])]
+                fn a(_: a::Context) {}
+
+                #[task(priority = 2, shared = [state.counter])]
+                fn b(_: b::Context) {}
+
+                #[task(priority = 3, shared = [&state.config])]
+                fn c(_: c::Context) {}
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    let counter = FieldPath {
+        resource: ident("state"),
+        fields: vec![ident("counter")],
+    };
+    let config = FieldPath {
+        resource: ident("state"),
+        fields: vec![ident("config")],
+    };
+    assert!(counter.overlaps(&FieldPath::resource(ident("state"))));
+    assert!(!counter.overlaps(&config));
+    assert_eq!(counter.to_string(), "state.counter");
+
+    assert_eq!(app.resources_of(&ident("a")).unwrap(), [&ident("state")]);
+    assert_eq!(app.tasks_accessing(&ident("state")).count(), 3);
+
+    assert!(analysis.ownerships.is_empty());
+    assert_eq!(
+        analysis.field_ownerships[&counter],
+        Ownership::Contended { ceiling: 2 }
+    );
+    assert_eq!(
+        analysis.field_ownerships[&config],
+        Ownership::Contended { ceiling: 3 }
+    );
+    assert!(analysis
+        .shared_resource_locations
+        .contains_key(&ident("state")));
+
+    let a = &analysis.field_locks[&ident("a")];
+    assert_eq!(a[&counter].ceiling, 2);
+    assert!(a[&counter].needs_lock);
+    assert_eq!(a[&config].ceiling, 3);
+    assert!(a[&config].read_only);
+    assert!(!analysis.field_locks[&ident("b")][&counter].needs_lock);
+}
+
+#[test]
+fn field_critical_sections() {
+    // on a hosted target every contended lock is a global critical section, fields included
+    let (_app, analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[shared]
+                struct Shared {
+                    state: State,
+                }
+
+                #[local]
+                struct Local {}
+
+                #[init]
+                fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+                #[task(priority = 1, shared = [state.counter])]
+                fn a(_: a::Context) {}
+
+                #[task(priority = 2, shared = [state.counter])]
+                fn b(_: b::Context) {}
+            }
+        ),
+        Settings {
+            target: Target::Host,
+            ..Settings::default()
+        },
+    )
+    .unwrap();
+
+    let counter = FieldPath {
+        resource: ident("state"),
+        fields: vec![ident("counter")],
+    };
+    assert!(analysis.critical_sections.contains(&ident("state")));
+    assert!(analysis.field_locks[&ident("a")][&counter].critical_section);
+    assert!(!analysis.field_locks[&ident("b")][&counter].critical_section);
+}
+
+#[test]
+fn normalized_types() {
+    let (app, analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                use core::primitive::u32 as Word;
+                use hal::{
+                    serial::{self, Serial},
+                    Uart as _,
+                };
+
+                #[shared]
+                struct Shared {
+                    a: u32,
+                    b: Word,
+                    c: ::core::primitive::u32,
+                }
+
+                #[local]
+                struct Local {}
+
+                #[init]
+                fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+                #[task(priority = 1, shared = [a, b, c])]
+                fn foo(_: foo::Context) {}
+
+                #[task(priority = 2, shared = [a, b, c])]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    assert_eq!(analysis.send_types.len(), 1);
+    let ty = analysis.send_types.keys().next().unwrap();
+    assert_eq!(quote!(#ty).to_string(), "u32");
+
+    let normalize = |ty: syn::Type| {
+        let ty = app.normalize_type(&ty).normalized;
+        quote!(#ty).to_string()
+    };
+    assert_eq!(
+        normalize(syn::parse_quote!(&'static mut [(Serial<Word>)])),
+        "& 'static mut [hal :: serial :: Serial < u32 >]"
+    );
+    assert_eq!(
+        normalize(syn::parse_quote!(serial::Rx)),
+        "hal :: serial :: Rx"
+    );
+    assert_eq!(normalize(syn::parse_quote!(Uart)), "Uart");
+}
+
+#[test]
+fn send_reasons() {
+    let (app, analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[shared]
+                struct Shared {}
+
+                #[local]
+                struct Local {
+                    led: Led,
+                }
+
+                #[init(spawn = [foo])]
+                fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+                #[task(priority = 2, local = [led], spawn = [bar])]
+                fn foo(_: foo::Context, _: X) {}
+
+                #[task(priority = 2)]
+                fn bar(_: bar::Context, _: Y) {}
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        app.software_tasks[&ident("foo")]
+            .args
+            .spawn
+            .iter()
+            .collect::<Vec<_>>(),
+        [&ident("bar")]
+    );
+    assert_eq!(analysis.send_types.len(), 2);
+
+    let x = app.normalize_type(&syn::parse_quote!(X));
+    assert_eq!(
+        analysis.send_types[&x],
+        [SendReason::TaskInput {
+            task: ident("foo"),
+            priority: 2,
+            spawners: Some(vec![(ident("init"), 0)]),
+        }]
+    );
+
+    let led = app.normalize_type(&syn::parse_quote!(Led));
+    assert_eq!(
+        analysis.send_types[&led],
+        [SendReason::LocalResource {
+            resource: ident("led"),
+            users: vec![(ident("foo"), 2)],
+        }]
+    );
+
+    // `bar` is only spawned from its own priority
+    let y = app.normalize_type(&syn::parse_quote!(Y));
+    assert!(!analysis.send_types.contains_key(&y));
+}
+
+fn ident(name: &str) -> Ident {
+    Ident::new(name, Span::call_site())
+}
@@ -0,0 +1,49 @@
+use std::process::Command;
+
+fn check(args: &[&str]) -> (i32, String, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_rtic-syntax-check"))
+        .args(args)
+        .output()
+        .unwrap();
+
+    (
+        output.status.code().unwrap(),
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn valid() {
+    let (code, stdout, _) = check(&["tests/snapshots/locks.rs"]);
+    assert_eq!(code, 0);
+    assert!(stdout.starts_with("tests/snapshots/locks.rs: ok: app `app` with 0 hardware task(s)"));
+}
+
+#[test]
+fn invalid() {
+    let (code, _, stderr) = check(&["--quiet", "tests/check/undeclared.rs"]);
+    assert_eq!(code, 1);
+    assert_eq!(
+        stderr,
+        "tests/check/undeclared.rs:16:22: error: this shared resource has NOT been declared; did you mean `counter`?\n"
+    );
+}
+
+#[test]
+fn assigned_priorities() {
+    let (code, stdout, _) = check(&["tests/check/deadlines.rs"]);
+    assert_eq!(code, 0);
+    assert!(stdout.ends_with(
+        "tests/check/deadlines.rs: note: task `slow` was assigned priority 1; pin it with `priority = 1`
+tests/check/deadlines.rs: note: task `fast` was assigned priority 2; pin it with `priority = 2`
+"
+    ));
+}
+
+#[test]
+fn usage() {
+    assert_eq!(check(&[]).0, 2);
+    assert_eq!(check(&["--target", "avr", "foo.rs"]).0, 2);
+    assert_eq!(check(&["tests/check/missing.rs"]).0, 2);
+}
@@ -0,0 +1,17 @@
+#[rtic::app(device = lm3s6965, dispatchers = [UART0, UART1])]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[task(priority = auto, deadline = 10.millis())]
+    fn slow(_: slow::Context) {}
+
+    #[task(priority = auto, deadline = 1.millis())]
+    fn fast(_: fast::Context) {}
+}
@@ -0,0 +1,18 @@
+//! Misspelled shared resource
+
+#[rtic::app(device = x)]
+mod app {
+    #[shared]
+    struct Shared {
+        counter: u32,
+    }
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[task(shared = [countr])]
+    fn foo(_: foo::Context) {}
+}
@@ -0,0 +1,11 @@
+use rtic_syntax::testing;
+
+#[test]
+fn examples() {
+    testing::assert_snapshot("examples", "tests/snapshots/examples");
+}
+
+#[test]
+fn snapshots() {
+    testing::assert_snapshot("tests/snapshots", "tests/snapshots");
+}
@@ -0,0 +1,34 @@
+//! Priority 0 tasks run inside `idle` and share its resources without locks
+
+#[mock::app(dispatchers = [UART0])]
+mod app {
+    #[shared]
+    struct Shared {
+        log: Log,
+        counter: u32,
+    }
+
+    #[local]
+    struct Local {
+        buffer: Buffer,
+    }
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[idle(shared = [log])]
+    fn idle(_: idle::Context) -> ! {
+        loop {
+            rtic::yield_now();
+        }
+    }
+
+    #[task(priority = 0, shared = [log], local = [buffer])]
+    fn flush(_: flush::Context) {}
+
+    #[task(priority = 0, capacity = 4)]
+    fn compact(_: compact::Context, level: u8) {}
+
+    #[task(priority = 2, shared = [counter])]
+    fn tick(_: tick::Context) {}
+}
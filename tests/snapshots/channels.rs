@@ -0,0 +1,32 @@
+//! Message channels between tasks at the same and at different priorities
+
+#[mock::app(parse_binds)]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[channel(capacity = 8)]
+    type Samples = u16;
+
+    #[channel(capacity = 2)]
+    type Commands = Command;
+
+    #[cfg(feature = "log")]
+    #[channel(capacity = 16)]
+    type Log = &'static str;
+
+    #[task(binds = Adc, priority = 3, send = [Samples])]
+    fn adc(_: adc::Context) {}
+
+    #[task(priority = 1, recv = [Samples], send = [Commands])]
+    fn filter(_: filter::Context) {}
+
+    #[task(priority = 1, recv = [Commands])]
+    fn control(_: control::Context) {}
+}
@@ -0,0 +1,37 @@
+//! Priorities assigned from deadlines and periods, around an explicit priority
+
+#[mock::app(parse_binds, dispatchers = [UART0, UART1, UART2], priorities = "deadline-monotonic")]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[monotonic(binds = Tim1, default = true)]
+    type Mono = hal::Tim1Monotonic;
+
+    #[task(binds = Uart3, deadline = 100.micros())]
+    fn rx(_: rx::Context) {}
+
+    #[task(period = 10.millis())]
+    fn control(_: control::Context) {}
+
+    #[task(priority = auto, deadline = 1.millis())]
+    fn filter(_: filter::Context) {}
+
+    #[task(priority = 4, deadline = 5.millis())]
+    fn pinned(_: pinned::Context) {}
+
+    #[task(period = 1.secs(), deadline = 10.millis())]
+    fn log(_: log::Context) {}
+
+    #[task]
+    fn housekeeping(_: housekeeping::Context) {}
+
+    #[task(priority = 2)]
+    fn manual(_: manual::Context) {}
+}
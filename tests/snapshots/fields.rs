@@ -0,0 +1,25 @@
+//! Fields of a shared struct locked on their own get ceilings of their own
+
+#[mock::app(dispatchers = [UART0])]
+mod app {
+    #[shared]
+    struct Shared {
+        state: State,
+        log: Log,
+    }
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[task(priority = 1, shared = [state.counter, &state.config])]
+    fn count(_: count::Context) {}
+
+    #[task(priority = 2, shared = [state.counter, log])]
+    fn report(_: report::Context) {}
+
+    #[task(priority = 3, shared = [&state.config, state.stats.errors])]
+    fn fault(_: fault::Context) {}
+}
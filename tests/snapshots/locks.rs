@@ -0,0 +1,27 @@
+//! Per-context lock table
+
+#[mock::app]
+mod app {
+    #[shared]
+    struct Shared {
+        a: u32,
+        #[lock_free]
+        b: u32,
+        c: u32,
+    }
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[idle(shared = [a])]
+    fn idle(_: idle::Context) -> ! {}
+
+    #[task(priority = 2, shared = [a, b, &c])]
+    fn foo(_: foo::Context) {}
+
+    #[task(priority = 2, shared = [b, &c])]
+    fn bar(_: bar::Context) {}
+}
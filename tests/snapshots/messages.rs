@@ -0,0 +1,29 @@
+//! Messages are the ordered input types of software tasks
+
+#[mock::app(dispatchers = [UART0])]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[task(capacity = 2)]
+    fn log(_: log::Context, level: Level, text: &'static str, mut count: u32) {}
+
+    #[task]
+    fn run(
+        _: run::Context,
+        job: Box<dyn FnOnce() + Send>,
+        (x, y): (i16, i16),
+        Point { x: px, .. }: Point,
+        name: &'static dyn core::fmt::Display,
+        _: [u8; 4],
+        samples: Box<[u16]>,
+        table: &'static [u8],
+    ) {
+    }
+}
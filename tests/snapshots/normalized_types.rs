@@ -0,0 +1,26 @@
+//! Types are listed once however they are written
+
+#[mock::app(dispatchers = [UART0])]
+mod app {
+    use core::primitive::u32 as Word;
+    use heapless::{spsc::Queue, Vec as Buffer};
+
+    #[shared]
+    struct Shared {
+        count: u32,
+        total: Word,
+        samples: heapless::Vec<core::primitive::u8, 8>,
+    }
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[task(priority = 1, shared = [count, total, samples])]
+    fn low(_: low::Context, _: ::core::primitive::u32) {}
+
+    #[task(priority = 2, shared = [count, total, samples])]
+    fn high(_: high::Context, _: Buffer<u8, 8>, _: Queue<Word, 4>) {}
+}
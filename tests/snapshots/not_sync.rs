@@ -0,0 +1,21 @@
+//! Resources shared at a single priority needn't be `Sync`
+
+#[mock::app]
+mod app {
+    #[shared]
+    struct Shared {
+        x: i32,
+    }
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[task(shared = [x])]
+    fn foo(_: foo::Context) {}
+
+    #[task(shared = [x])]
+    fn bar(_: bar::Context) {}
+}
@@ -0,0 +1,28 @@
+//! Periodic tasks on an explicit and on the default monotonic
+
+#[mock::app]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[monotonic(binds = Tim1, default = true)]
+    type Fast = hal::Tim1Monotonic;
+
+    #[monotonic(binds = Tim2)]
+    type Slow = hal::Tim2Monotonic;
+
+    #[task(period = 10.millis())]
+    fn blink(_: blink::Context) {}
+
+    #[task(priority = 2, monotonic = Slow, period = 1.secs(), offset = 100.millis())]
+    fn log(_: log::Context) {}
+
+    #[task]
+    fn once(_: once::Context, x: u32) {}
+}
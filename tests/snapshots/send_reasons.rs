@@ -0,0 +1,45 @@
+//! `Send` is only required for values that move between priorities
+
+#[mock::app(parse_binds, dispatchers = [UART0, UART1])]
+mod app {
+    #[shared]
+    struct Shared {
+        config: Config,
+        journal: Journal,
+    }
+
+    #[local]
+    struct Local {
+        led: Led,
+        scratch: Scratch,
+        unused: Unused,
+    }
+
+    #[channel(capacity = 4)]
+    type Events = Event;
+
+    #[channel(capacity = 4)]
+    type Alarms = Alarm;
+
+    #[init(spawn = [blink])]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[idle(shared = [journal], local = [scratch], spawn = [log])]
+    fn idle(_: idle::Context) -> ! {
+        loop {
+            rtic::yield_now();
+        }
+    }
+
+    #[task(binds = UART2, priority = 2, shared = [config], local = [led], send = [Alarms])]
+    fn uart(_: uart::Context) {}
+
+    #[task(priority = 0, shared = [journal])]
+    fn log(_: log::Context, entry: Entry) {}
+
+    #[task(priority = 1, shared = [config], send = [Events], recv = [Alarms], spawn = [blink])]
+    fn blink(_: blink::Context, pattern: Pattern) {}
+
+    #[task(priority = 1, recv = [Events])]
+    fn react(_: react::Context, event: Kind) {}
+}
@@ -0,0 +1,19 @@
+//! Messages sent between priorities must be `Send`
+
+#[mock::app]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[task(priority = 2)]
+    fn foo(_: foo::Context) {}
+
+    #[task]
+    fn bar(_: bar::Context, _: X) {}
+}
@@ -0,0 +1,21 @@
+//! Resources shared by reference between priorities must be `Sync`
+
+#[mock::app]
+mod app {
+    #[shared]
+    struct Shared {
+        x: i32,
+    }
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[task(shared = [&x])]
+    fn foo(_: foo::Context) {}
+
+    #[task(priority = 2, shared = [&x])]
+    fn bar(_: bar::Context) {}
+}
@@ -0,0 +1,22 @@
+//! Task list with bindings and dispatchers
+
+#[mock::app(parse_binds, dispatchers = [SSI0])]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[task(priority = 3)]
+    fn low(_: low::Context) {}
+
+    #[task(binds = UART0, priority = 2)]
+    fn uart0(_: uart0::Context) {}
+
+    #[task(priority = 5)]
+    fn high(_: high::Context) {}
+}
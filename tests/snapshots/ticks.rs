@@ -0,0 +1,34 @@
+//! Periodic tasks whose durations are converted into monotonic ticks
+
+#[mock::app]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[monotonic(binds = Tim1, default = true, tick_hz = 32_768, bits = 32)]
+    type Rtc = hal::Tim1Monotonic;
+
+    #[monotonic(binds = Tim2, tick_hz = 1_000_000)]
+    type Fast = hal::Tim2Monotonic;
+
+    #[monotonic(binds = Tim3)]
+    type Untimed = hal::Tim3Monotonic;
+
+    #[task(period = 1.secs(), offset = 500.millis())]
+    fn blink(_: blink::Context) {}
+
+    #[task(monotonic = Fast, period = 250.micros())]
+    fn sample(_: sample::Context) {}
+
+    #[task(monotonic = Untimed, period = 10.millis())]
+    fn log(_: log::Context) {}
+
+    #[task(monotonic = Fast, period = PERIOD)]
+    fn poll(_: poll::Context) {}
+}
@@ -0,0 +1,25 @@
+//! Timer queues of monotonics with and without dedicated tasks
+
+#[mock::app]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[monotonic(binds = Tim1, default = true)]
+    type Fast = hal::Tim1Monotonic;
+
+    #[monotonic(binds = Tim2)]
+    type Slow = hal::Tim2Monotonic;
+
+    #[task(capacity = 2)]
+    fn foo(_: foo::Context) {}
+
+    #[task(priority = 4, capacity = 3, monotonic = Fast)]
+    fn bar(_: bar::Context) {}
+}
@@ -4,4 +4,5 @@ use trybuild::TestCases;
 fn ui() {
     let t = TestCases::new();
     t.compile_fail("ui/*.rs");
+    t.pass("ui/pass/*.rs");
 }
@@ -0,0 +1,4 @@
+#![no_main]
+
+#[mock::app(priorities = "rate-monotonic")]
+mod app {}
@@ -0,0 +1,20 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[cfg(feature = "adc")]
+    #[channel(capacity = 4)]
+    type Samples = u16;
+
+    #[task(send = [Samples])]
+    fn foo(_: foo::Context) {}
+}
@@ -0,0 +1,25 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[channel(capacity = 4)]
+    type Samples = u16;
+
+    #[task(send = [Samples])]
+    fn producer(_: producer::Context) {}
+
+    #[task(recv = [Samples])]
+    fn foo(_: foo::Context) {}
+
+    #[task(priority = 2, recv = [Samples])]
+    fn bar(_: bar::Context) {}
+}
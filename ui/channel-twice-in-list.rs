@@ -0,0 +1,19 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[channel(capacity = 4)]
+    type Samples = u16;
+
+    #[task(recv = [Samples, Samples])]
+    fn foo(_: foo::Context) {}
+}
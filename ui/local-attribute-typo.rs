@@ -0,0 +1,10 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[local]
+    struct Local {
+        #[lock_fre]
+        a: u32,
+    }
+}
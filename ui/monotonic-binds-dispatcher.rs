@@ -0,0 +1,16 @@
+#![no_main]
+
+#[mock::app(dispatchers = [Tim1])]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[monotonic(binds = Tim1)]
+    type Fast = hal::Tim1Monotonic;
+}
@@ -0,0 +1,10 @@
+#![no_main]
+
+#[mock::app(parse_binds, cortex_m)]
+mod app {
+    #[monotonic(binds = SysTick)]
+    type Mono = hal::Systick;
+
+    #[task(binds = SysTick)]
+    fn foo(_: foo::Context) {}
+}
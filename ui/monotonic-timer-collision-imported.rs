@@ -0,0 +1,12 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[monotonic(binds = Tim1)]
+    type Fast1 = hal::Tim1Monotonic;
+
+    #[monotonic(binds = Tim2)]
+    type Fast2 = Timer;
+
+    use hal::{Tim1Monotonic as Timer, Tim2Monotonic};
+}
@@ -0,0 +1,22 @@
+#[mock::app]
+mod app {
+    #[shared]
+    struct Shared {
+        #[cfg(feature = "x")]
+        x: u32,
+    }
+
+    #[local]
+    struct Local {
+        #[cfg(feature = "x")]
+        z: u32,
+    }
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[idle(shared = [x], local = [z])]
+    fn idle(_: idle::Context) -> ! {
+        loop {}
+    }
+}
@@ -0,0 +1,16 @@
+use std::{rc::Rc, sync::Arc};
+
+#[mock::app(dispatchers = [UART0])]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[task]
+    fn foo(_: foo::Context, handler: Arc<dyn Handler + Send>, bytes: Rc<[u8]>) {}
+}
@@ -0,0 +1,10 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[shared]
+    struct Shared {
+        #[lock_fre]
+        a: u32,
+    }
+}
@@ -0,0 +1,20 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[shared]
+    struct Shared {
+        #[cfg(feature = "y")]
+        x: u32,
+    }
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[cfg(feature = "x")]
+    #[task(shared = [x])]
+    fn foo(_: foo::Context) {}
+}
@@ -0,0 +1,21 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[shared]
+    struct Shared {
+        state: State,
+    }
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[task(shared = [state])]
+    fn a(_: a::Context) {}
+
+    #[task(shared = [state.counter])]
+    fn b(_: b::Context) {}
+}
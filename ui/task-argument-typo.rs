@@ -0,0 +1,7 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[task(prority = 2)]
+    fn foo(_: foo::Context) {}
+}
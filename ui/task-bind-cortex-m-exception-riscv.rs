@@ -0,0 +1,16 @@
+#![no_main]
+
+#[mock::app(parse_binds, riscv_plic, device_vectors = [UART0])]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[task(binds = SysTick)]
+    fn foo(_: foo::Context) {}
+}
@@ -0,0 +1,16 @@
+#![no_main]
+
+#[mock::app(parse_binds, riscv_plic)]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[task(binds = MachineExternal)]
+    fn foo(_: foo::Context) {}
+}
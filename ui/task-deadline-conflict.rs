@@ -0,0 +1,19 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[task(priority = auto, deadline = 10.millis())]
+    fn slow(_: slow::Context) {}
+
+    #[task(priority = 1, deadline = 1.millis())]
+    fn fast(_: fast::Context) {}
+}
@@ -0,0 +1,37 @@
+#![no_main]
+
+#[mock::app(riscv_plic, priorities = "deadline-monotonic")]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[task(deadline = 1.millis())]
+    fn t1(_: t1::Context) {}
+
+    #[task(deadline = 2.millis())]
+    fn t2(_: t2::Context) {}
+
+    #[task(deadline = 3.millis())]
+    fn t3(_: t3::Context) {}
+
+    #[task(deadline = 4.millis())]
+    fn t4(_: t4::Context) {}
+
+    #[task(deadline = 5.millis())]
+    fn t5(_: t5::Context) {}
+
+    #[task(deadline = 6.millis())]
+    fn t6(_: t6::Context) {}
+
+    #[task(deadline = 7.millis())]
+    fn t7(_: t7::Context) {}
+
+    #[task(deadline = 8.millis())]
+    fn t8(_: t8::Context) {}
+}
@@ -0,0 +1,16 @@
+#![no_main]
+
+#[mock::app(parse_binds, cortex_m)]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[task(binds = NMI, priority = 2)]
+    fn nmi(_: nmi::Context) {}
+}
@@ -0,0 +1,18 @@
+#![no_main]
+
+#[mock::app(parse_binds, cortex_m)]
+mod app {
+    #[shared]
+    struct Shared {
+        a: u32,
+    }
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[task(binds = NMI, shared = [a])]
+    fn nmi(_: nmi::Context) {}
+}
@@ -0,0 +1,19 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[monotonic(binds = Tim1)]
+    type Mono = hal::Tim1Monotonic;
+
+    #[task(monotonic = Mno)]
+    fn foo(_: foo::Context) {}
+}
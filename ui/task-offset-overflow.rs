@@ -0,0 +1,19 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[monotonic(binds = Tim1, tick_hz = 1_000, bits = 8)]
+    type Mono = hal::Tim1Monotonic;
+
+    #[task(monotonic = Mono, period = 100.millis(), offset = 1.secs())]
+    fn foo(_: foo::Context) {}
+}
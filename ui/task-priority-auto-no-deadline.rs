@@ -0,0 +1,7 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[task(priority = auto)]
+    fn foo(_: foo::Context) {}
+}
@@ -0,0 +1,7 @@
+#![no_main]
+
+#[mock::app(riscv_plic)]
+mod app {
+    #[task(priority = 8)]
+    fn foo(_: foo::Context) {}
+}
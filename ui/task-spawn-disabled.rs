@@ -0,0 +1,20 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[task(spawn = [blink])]
+    fn foo(_: foo::Context) {}
+
+    #[cfg(feature = "blink")]
+    #[task]
+    fn blink(_: blink::Context) {}
+}
@@ -0,0 +1,19 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {}
+
+    #[task(spawn = [blink, blink])]
+    fn foo(_: foo::Context) {}
+
+    #[task]
+    fn blink(_: blink::Context) {}
+}